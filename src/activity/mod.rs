@@ -0,0 +1,113 @@
+//! Reading the activity log, which records who added, updated, completed or deleted which tasks,
+//! projects and comments, and when.
+//!
+//! - To get a page of events, newest first, use `get_activity()` with an `ActivityQuery`
+//! - To get every event matching a query, use `get_all_activity()`
+//!
+//! Events refer to objects by the same ID strings the rest of the crate uses, so an event's
+//! `object_id` can be given to e.g. `tasks::get_individual_task_by_id()` if the object still exists.
+//! ```no_run
+//! use todoist_v2_rest::{TodoistUser, activity};
+//! let user = TodoistUser::new("a2a72c2f394b265bb798d5dc4ef55be51443d519");
+//! let query = activity::ActivityQuery {
+//!     object_type: Some(activity::ActivityObjectType::Task),
+//!     event_type: Some(activity::ActivityEventType::Completed),
+//!     parent_project_id: Some("2203306141".to_string()),
+//!     since: Some("2024-01-01".to_string()),
+//!     ..Default::default()
+//! };
+//! for event in activity::get_all_activity(&user, &query).expect("Couldn't load activity") {
+//!     println!("{} completed by {:?} at {}", event.object_id, event.initiator_id, event.event_date);
+//! }
+//! ```
+//!
+//! The activity log is only available to Todoist Pro and Business users.
+
+
+mod structs;
+
+pub use structs::activity_event::{ActivityEvent, ActivityEventType, ActivityObjectType};
+pub use structs::activity_query::ActivityQuery;
+
+use crate::err::TodoistAPIError;
+use crate::general::get_from_response;
+use crate::TodoistUser;
+#[cfg(feature = "async")]
+use crate::AsyncTodoistClient;
+use structs::activity_query::ActivityResponse;
+
+/// The most events Todoist returns in one page, used as the page size by `get_all_activity()`.
+pub const ACTIVITY_PAGE_LIMIT: u32 = 100;
+
+
+/// Get a page of events, newest first, filtered and limited by the query. Events outside the
+/// query's date range are left out after the page is downloaded, so the page may be shorter than
+/// its `limit` even if there are more events to come. To get every matching event rather than one
+/// page, use `get_all_activity()`.
+pub fn get_activity(user: &TodoistUser, query: &ActivityQuery) -> Result<Vec<ActivityEvent>, TodoistAPIError> {
+    // Send the API request
+    let response = user.send(query.request());
+    let mut events = get_from_response::<ActivityResponse>(response)?.events;
+    events.retain(|event| !query.is_too_early(event) && !query.is_too_late(event));
+    Ok(events)
+}
+
+/// Asynchronous version of `get_activity()`.
+#[cfg(feature = "async")]
+pub async fn get_activity_async(client: &AsyncTodoistClient, query: &ActivityQuery) -> Result<Vec<ActivityEvent>, TodoistAPIError> {
+    let response = client.send(query.request()).await;
+    let mut events = get_from_response::<ActivityResponse>(response)?.events;
+    events.retain(|event| !query.is_too_early(event) && !query.is_too_late(event));
+    Ok(events)
+}
+
+
+/// Get every event matching the query, newest first, requesting one page after another until
+/// there are no more, or until the pages reach events from before the query's `since` date. The
+/// query's `limit` is used as the size of each page (100 if it isn't given, and at most 100, which
+/// is all Todoist returns), and its `offset` as where to start.
+pub fn get_all_activity(user: &TodoistUser, query: &ActivityQuery) -> Result<Vec<ActivityEvent>, TodoistAPIError> {
+    let mut query = first_activity_page(query);
+    let mut events = Vec::new();
+    loop {
+        // Send the API request
+        let response = user.send(query.request());
+        let page = get_from_response::<ActivityResponse>(response)?.events;
+        if !next_activity_page(&mut query, &mut events, page) {
+            return Ok(events);
+        }
+    }
+}
+
+/// Asynchronous version of `get_all_activity()`.
+#[cfg(feature = "async")]
+pub async fn get_all_activity_async(client: &AsyncTodoistClient, query: &ActivityQuery) -> Result<Vec<ActivityEvent>, TodoistAPIError> {
+    let mut query = first_activity_page(query);
+    let mut events = Vec::new();
+    loop {
+        let response = client.send(query.request()).await;
+        let page = get_from_response::<ActivityResponse>(response)?.events;
+        if !next_activity_page(&mut query, &mut events, page) {
+            return Ok(events);
+        }
+    }
+}
+
+/// The query for the first page of `get_all_activity()`.
+fn first_activity_page(query: &ActivityQuery) -> ActivityQuery {
+    ActivityQuery {
+        limit: Some(query.limit.unwrap_or(ACTIVITY_PAGE_LIMIT).min(ACTIVITY_PAGE_LIMIT)),
+        ..query.clone()
+    }
+}
+
+/// Add the events in a page which are in the query's date range to those so far, and move the
+/// query on to the next page. Returns whether there might be more events in range, which there
+/// aren't if this page wasn't full or reached events from before the range.
+fn next_activity_page(query: &mut ActivityQuery, events: &mut Vec<ActivityEvent>, page: Vec<ActivityEvent>) -> bool {
+    let count = page.len() as u32;
+    let reached_start = page.last().is_some_and(|event| query.is_too_early(event));
+    events.extend(page.into_iter().filter(|event| !query.is_too_early(event) && !query.is_too_late(event)));
+    query.offset = Some(query.offset.unwrap_or(0) + count);
+    count > 0 && Some(count) == query.limit && !reached_start
+}
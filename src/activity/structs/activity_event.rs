@@ -0,0 +1,105 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+
+/// A record of something happening to a task, project or comment, as returned by
+/// `get_activity()`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ActivityEvent {
+    /// The ID of the event.
+    #[serde(deserialize_with = "string_or_number")]
+    pub id: String,
+
+    /// The kind of object the event happened to.
+    pub object_type: ActivityObjectType,
+
+    /// The ID of the task, project or comment the event happened to.
+    pub object_id: String,
+
+    /// What happened.
+    pub event_type: ActivityEventType,
+
+    /// When it happened, in RFC3339 format in UTC.
+    pub event_date: String,
+
+    /// The ID of the project the object is (or was) in, if it isn't a project itself.
+    pub parent_project_id: Option<String>,
+
+    /// The ID of the task the object is (or was) on, if it is a comment on a task.
+    #[serde(rename = "parent_item_id")]
+    pub parent_task_id: Option<String>,
+
+    /// The ID of the user who made the change, if it wasn't the user themself.
+    pub initiator_id: Option<String>,
+
+    /// Details of the event which depend on its type, such as the content of the task or the
+    /// values of any fields which were updated.
+    #[serde(default)]
+    pub extra_data: Map<String, Value>,
+}
+
+/// The kind of object an activity event happened to. Todoist calls tasks "items" and comments
+/// "notes".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub enum ActivityObjectType {
+    #[serde(rename = "item")]
+    Task,
+    #[serde(rename = "project")]
+    Project,
+    #[serde(rename = "note")]
+    Comment,
+}
+
+/// What happened to the object in an activity event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(missing_docs)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityEventType {
+    Added,
+    Updated,
+    Deleted,
+    Completed,
+    Uncompleted,
+    Archived,
+    Unarchived,
+    Shared,
+    Left,
+}
+
+impl ActivityObjectType {
+    /// The name Todoist uses for the object type.
+    pub(crate) fn api_name(self) -> &'static str {
+        match self {
+            ActivityObjectType::Task => "item",
+            ActivityObjectType::Project => "project",
+            ActivityObjectType::Comment => "note",
+        }
+    }
+}
+
+impl ActivityEventType {
+    /// The name Todoist uses for the event type.
+    pub(crate) fn api_name(self) -> &'static str {
+        match self {
+            ActivityEventType::Added => "added",
+            ActivityEventType::Updated => "updated",
+            ActivityEventType::Deleted => "deleted",
+            ActivityEventType::Completed => "completed",
+            ActivityEventType::Uncompleted => "uncompleted",
+            ActivityEventType::Archived => "archived",
+            ActivityEventType::Unarchived => "unarchived",
+            ActivityEventType::Shared => "shared",
+            ActivityEventType::Left => "left",
+        }
+    }
+}
+
+
+/// Deserialise an ID which Todoist may send as either a string or a number.
+fn string_or_number<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::String(id) => Ok(id),
+        Value::Number(id) => Ok(id.to_string()),
+        other => Err(serde::de::Error::custom(format!("expected an ID, found {}", other))),
+    }
+}
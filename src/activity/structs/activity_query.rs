@@ -0,0 +1,81 @@
+use serde::Deserialize;
+use crate::activity::{ActivityEvent, ActivityEventType, ActivityObjectType};
+use crate::general::ApiRequest;
+
+/// Which events to return from `get_activity()`. Every field is optional, so start from
+/// `ActivityQuery::default()` and fill in the ones needed.
+///
+/// Dates are given in UTC as `YYYY-MM-DDTHH:MM:SS`, or cut short (e.g. `2024-01-31` or
+/// `2024-01-31T09:00`), and compared with each event's `event_date`. Todoist can't filter by date
+/// itself, so events outside the range are dropped after they are downloaded.
+#[derive(Debug, Clone, Default)]
+pub struct ActivityQuery {
+    /// Only return events which happened to this kind of object.
+    pub object_type: Option<ActivityObjectType>,
+
+    /// Only return this kind of event.
+    pub event_type: Option<ActivityEventType>,
+
+    /// Only return events which happened to the object with this ID. Todoist needs `object_type`
+    /// to be given too.
+    pub object_id: Option<String>,
+
+    /// Only return events which happened to objects in the project with this ID.
+    pub parent_project_id: Option<String>,
+
+    /// Only return events which happened at or after this date.
+    pub since: Option<String>,
+
+    /// Only return events which happened before this date.
+    pub until: Option<String>,
+
+    /// The most events to request, up to 100. Todoist returns 30 if this isn't given.
+    pub limit: Option<u32>,
+
+    /// How many events to skip, for getting later pages of results.
+    pub offset: Option<u32>,
+}
+
+impl ActivityQuery {
+    /// Build the request for the page of events the query describes, without the date range.
+    pub(crate) fn request(&self) -> ApiRequest {
+        let mut request = ApiRequest::sync_get("/activity/get");
+        if let Some(object_type) = self.object_type {
+            request = request.query("object_type", object_type.api_name());
+        }
+        if let Some(event_type) = self.event_type {
+            request = request.query("event_type", event_type.api_name());
+        }
+        if let Some(object_id) = &self.object_id {
+            request = request.query("object_id", object_id);
+        }
+        if let Some(parent_project_id) = &self.parent_project_id {
+            request = request.query("parent_project_id", parent_project_id);
+        }
+        if let Some(limit) = self.limit {
+            request = request.query("limit", &limit.to_string());
+        }
+        if let Some(offset) = self.offset {
+            request = request.query("offset", &offset.to_string());
+        }
+        request
+    }
+
+    /// Whether the event happened after the end of the date range.
+    pub(crate) fn is_too_late(&self, event: &ActivityEvent) -> bool {
+        self.until.as_ref().is_some_and(|until| event.event_date.as_str() >= until.as_str())
+    }
+
+    /// Whether the event happened before the start of the date range.
+    pub(crate) fn is_too_early(&self, event: &ActivityEvent) -> bool {
+        self.since.as_ref().is_some_and(|since| event.event_date.as_str() < since.as_str())
+    }
+}
+
+
+/// The response to a request for events.
+#[derive(Debug, Deserialize)]
+pub(crate) struct ActivityResponse {
+    pub events: Vec<ActivityEvent>,
+}
+
@@ -0,0 +1,5 @@
+/// Defines the `ActivityEvent` struct and the types used within it.
+pub mod activity_event;
+
+/// Defines the `ActivityQuery` struct, for choosing which events to return.
+pub mod activity_query;
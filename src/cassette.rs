@@ -0,0 +1,372 @@
+//! Recording API interactions to a cassette file, and replaying them later without network access.
+//!
+//! A `RecordingTransport` wraps another transport (normally a `ReqwestTransport`) and keeps a copy
+//! of every request it sends and the response it gets back. Saving it writes these to a cassette:
+//! a JSON file which can be checked in alongside the tests that use it. The API token is never
+//! written; the `Authorization` header is replaced with `Bearer [REDACTED]`, as is any other
+//! appearance of the token in a request or response. The same goes for the OAuth secrets: the
+//! values of any `client_secret`, `code`, `access_token` or `refresh_token` field in a request or
+//! response body.
+//! ```no_run
+//! use todoist_v2_rest::{TodoistClient, tasks};
+//! use todoist_v2_rest::cassette::RecordingTransport;
+//! use todoist_v2_rest::transport::ReqwestTransport;
+//!
+//! let recorder = RecordingTransport::new(ReqwestTransport::new(reqwest::blocking::Client::new()));
+//! let client = TodoistClient::builder("a2a72c2f394b265bb798d5dc4ef55be51443d519")
+//!     .transport(recorder.clone())
+//!     .build()
+//!     .unwrap();
+//! tasks::get_all_active_tasks(&client).unwrap();
+//! recorder.save("tests/cassettes/active_tasks.json").unwrap();
+//! ```
+//!
+//! A `ReplayTransport` loaded from the cassette then answers the same requests with the recorded
+//! responses, so the test can run offline and always sees the same data. Each request is answered
+//! by the first recorded interaction with the same method, URL and body which hasn't been used
+//! yet; a request with no such interaction fails with a `TransportError`.
+//!
+//! Sync commands are the exception: each is sent with a random `uuid` (and new objects with a
+//! random `temp_id`), so a command batch built again in a later run has a different body to the
+//! one recorded, and won't replay. Submit it through a client with a pinned request ID (see
+//! `TodoistClient::with_request_id()`) when recording and replaying; its commands' IDs are then
+//! derived from the pinned ID, and are the same every time.
+//! ```no_run
+//! use todoist_v2_rest::{TodoistClient, tasks};
+//! use todoist_v2_rest::cassette::ReplayTransport;
+//!
+//! let replay = ReplayTransport::load("tests/cassettes/active_tasks.json").unwrap();
+//! let client = TodoistClient::builder("any token")
+//!     .transport(replay.clone())
+//!     .build()
+//!     .unwrap();
+//! let active = tasks::get_all_active_tasks(&client).unwrap();
+//! assert_eq!(replay.remaining(), 0);
+//! ```
+//!
+//! Both transports also work with an `AsyncTodoistClient` when the `async` feature is enabled.
+
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use crate::client::form_urlencoded;
+use crate::err::TodoistAPIError;
+pub use crate::general::REDACTED;
+use crate::transport::{HttpRequest, HttpResponse, HttpTransport};
+#[cfg(feature = "async")]
+use crate::transport::{AsyncHttpTransport, TransportFuture};
+
+/// The fields of request and response bodies whose values are redacted, as well as the API token.
+const SECRET_FIELDS: [&str; 4] = ["client_secret", "code", "access_token", "refresh_token"];
+
+
+/// A recording of a series of API interactions, as stored in a cassette file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Cassette {
+    /// The interactions, in the order they happened.
+    pub interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    /// Read a cassette from a JSON file.
+    pub fn load<P: AsRef<Path>>(path: P) -> std::io::Result<Cassette> {
+        let json = fs::read(path)?;
+        Ok(serde_json::from_slice(&json)?)
+    }
+
+    /// Write the cassette to a JSON file, replacing it if it exists.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let json = serde_json::to_vec_pretty(self)?;
+        fs::write(path, json)
+    }
+}
+
+
+/// A single request and the response it got.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    /// The request which was sent.
+    pub request: RecordedRequest,
+
+    /// The response which came back.
+    pub response: RecordedResponse,
+}
+
+
+/// A request as stored in a cassette.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    /// The HTTP method, e.g. `GET`.
+    pub method: String,
+
+    /// The full URL, including any query string.
+    pub url: String,
+
+    /// The headers which were sent, with the `Authorization` header redacted.
+    pub headers: Vec<(String, String)>,
+
+    /// The body, if there was one.
+    pub body: Option<String>,
+}
+
+
+/// A response as stored in a cassette.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    /// The HTTP status code.
+    pub status: u16,
+
+    /// The headers of the response.
+    pub headers: Vec<(String, String)>,
+
+    /// The body of the response.
+    pub body: String,
+}
+
+
+/// A transport which sends requests through another transport, keeping a redacted copy of every
+/// interaction to save as a cassette.
+///
+/// Clones share the same recording, so keep a clone to save after giving one to the client
+/// builder.
+pub struct RecordingTransport<T> {
+    inner: Arc<T>,
+    cassette: Arc<Mutex<Cassette>>,
+}
+
+impl<T> Clone for RecordingTransport<T> {
+    fn clone(&self) -> RecordingTransport<T> {
+        RecordingTransport {
+            inner: self.inner.clone(),
+            cassette: self.cassette.clone(),
+        }
+    }
+}
+
+impl<T> RecordingTransport<T> {
+    /// Record the requests sent through the given transport.
+    pub fn new(inner: T) -> RecordingTransport<T> {
+        RecordingTransport {
+            inner: Arc::new(inner),
+            cassette: Arc::new(Mutex::new(Cassette::default())),
+        }
+    }
+
+    /// A copy of everything recorded so far.
+    pub fn cassette(&self) -> Cassette {
+        self.lock().clone()
+    }
+
+    /// Write everything recorded so far to a cassette file, replacing it if it exists.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        self.lock().save(path)
+    }
+
+    /// Add an interaction to the recording. Failures to get any response aren't recorded.
+    fn record(&self, request: &HttpRequest, response: &Result<HttpResponse, TodoistAPIError>) {
+        if let Ok(response) = response {
+            self.lock().interactions.push(redacted_interaction(request, response));
+        }
+    }
+
+    /// Lock the recording, ignoring poisoning since a panicking test shouldn't hide other failures.
+    fn lock(&self) -> std::sync::MutexGuard<'_, Cassette> {
+        self.cassette.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<T: HttpTransport> HttpTransport for RecordingTransport<T> {
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, TodoistAPIError> {
+        let response = self.inner.send(request);
+        self.record(request, &response);
+        response
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T: AsyncHttpTransport> AsyncHttpTransport for RecordingTransport<T> {
+    fn send<'a>(&'a self, request: &'a HttpRequest) -> TransportFuture<'a> {
+        Box::pin(async move {
+            let response = self.inner.send(request).await;
+            self.record(request, &response);
+            response
+        })
+    }
+}
+
+
+/// A transport which answers requests with the responses recorded in a cassette, without any
+/// network access.
+///
+/// Clones share the same cassette, and the record of which interactions have been used.
+#[derive(Debug, Clone)]
+pub struct ReplayTransport {
+    state: Arc<Mutex<ReplayState>>,
+}
+
+/// The shared state of a `ReplayTransport`.
+#[derive(Debug)]
+struct ReplayState {
+    interactions: Vec<Interaction>,
+    used: Vec<bool>,
+}
+
+impl ReplayTransport {
+    /// Replay the interactions in the given cassette.
+    pub fn new(cassette: Cassette) -> ReplayTransport {
+        let used = vec![false; cassette.interactions.len()];
+        ReplayTransport {
+            state: Arc::new(Mutex::new(ReplayState { interactions: cassette.interactions, used })),
+        }
+    }
+
+    /// Replay the interactions in a cassette file.
+    pub fn load<P: AsRef<Path>>(path: P) -> std::io::Result<ReplayTransport> {
+        Ok(ReplayTransport::new(Cassette::load(path)?))
+    }
+
+    /// The number of recorded interactions which haven't been replayed yet.
+    pub fn remaining(&self) -> usize {
+        self.lock().used.iter().filter(|used| !**used).count()
+    }
+
+    /// Find the first unused interaction matching the request, and build its response.
+    fn replay(&self, request: &HttpRequest) -> Result<HttpResponse, TodoistAPIError> {
+        let mut state = self.lock();
+        // The recording has the request's secrets redacted, so redact them here too to compare
+        let secrets = request_secrets(request);
+        let url = redact(&request.url, &secrets);
+        let body = request.body.as_ref().map(|body| redact(&String::from_utf8_lossy(body), &secrets));
+        let index = (0..state.interactions.len()).find(|&i| {
+            let recorded = &state.interactions[i].request;
+            !state.used[i]
+                && recorded.method == request.method.as_str()
+                && recorded.url == url
+                && body_matches(recorded.body.as_deref(), body.as_deref())
+        });
+
+        let index = index.ok_or_else(|| TodoistAPIError::TransportError(format!(
+            "No recorded interaction left for {} {}", request.method, request.url
+        )))?;
+        state.used[index] = true;
+        response_from(&state.interactions[index].response)
+    }
+
+    /// Lock the state, ignoring poisoning since a panicking test shouldn't hide other failures.
+    fn lock(&self) -> std::sync::MutexGuard<'_, ReplayState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl HttpTransport for ReplayTransport {
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, TodoistAPIError> {
+        self.replay(request)
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncHttpTransport for ReplayTransport {
+    fn send<'a>(&'a self, request: &'a HttpRequest) -> TransportFuture<'a> {
+        Box::pin(async move { self.replay(request) })
+    }
+}
+
+
+/// Copy the request and response, replacing the secrets in either wherever they appear.
+fn redacted_interaction(request: &HttpRequest, response: &HttpResponse) -> Interaction {
+    let mut secrets = request_secrets(request);
+    secrets.extend(body_secrets(&response.body));
+    let redact = |text: &str| redact(text, &secrets);
+
+    let request = RecordedRequest {
+        method: request.method.to_string(),
+        url: redact(&request.url),
+        headers: request.headers.iter()
+            .map(|(name, value)| (name.clone(), redact(value)))
+            .collect(),
+        body: request.body.as_ref().map(|body| redact(&String::from_utf8_lossy(body))),
+    };
+    let response = RecordedResponse {
+        status: response.status.as_u16(),
+        headers: response.headers.iter()
+            .map(|(name, value)| (name.to_string(), redact(&String::from_utf8_lossy(value.as_bytes()))))
+            .collect(),
+        body: redact(&response.text()),
+    };
+    Interaction { request, response }
+}
+
+/// The secrets in a request: the API token, and the values of any `SECRET_FIELDS` in its body.
+fn request_secrets(request: &HttpRequest) -> Vec<String> {
+    let token = request.header(AUTHORIZATION.as_str())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::to_string);
+    let body = request.body.as_deref().map(body_secrets).unwrap_or_default();
+    token.into_iter().chain(body).collect()
+}
+
+/// The values of any `SECRET_FIELDS` at the top level of a JSON or form body, in both their plain
+/// and form encoded forms.
+fn body_secrets(body: &[u8]) -> Vec<String> {
+    let fields: Vec<(String, String)> = match serde_json::from_slice::<serde_json::Value>(body) {
+        Ok(serde_json::Value::Object(fields)) => fields.into_iter()
+            .filter_map(|(name, value)| value.as_str().map(|value| (name, value.to_string())))
+            .collect(),
+        Ok(_) => Vec::new(),
+        Err(_) => reqwest::Url::parse(&format!("http://localhost/?{}", String::from_utf8_lossy(body)))
+            .map(|url| url.query_pairs().map(|(name, value)| (name.into_owned(), value.into_owned())).collect())
+            .unwrap_or_default(),
+    };
+    fields.into_iter()
+        .filter(|(name, _)| SECRET_FIELDS.contains(&name.as_str()))
+        .flat_map(|(name, value)| {
+            let encoded = form_urlencoded(&[(name, value.clone())]);
+            let encoded = encoded.split_once('=').map(|(_, value)| value.to_string()).unwrap_or_default();
+            [value, encoded]
+        })
+        .collect()
+}
+
+/// Replace every appearance of the secrets in the text, longest first so that a secret containing
+/// another is replaced whole.
+fn redact(text: &str, secrets: &[String]) -> String {
+    let mut secrets: Vec<&String> = secrets.iter().filter(|secret| !secret.is_empty()).collect();
+    secrets.sort_by_key(|secret| std::cmp::Reverse(secret.len()));
+    secrets.into_iter().fold(text.to_string(), |text, secret| text.replace(secret.as_str(), REDACTED))
+}
+
+/// Whether a recorded request body matches the body of a new request. JSON bodies are compared as
+/// JSON, so that the order of their fields doesn't matter.
+fn body_matches(recorded: Option<&str>, body: Option<&str>) -> bool {
+    match (recorded, body) {
+        (Some(recorded), Some(body)) => {
+            match (serde_json::from_str::<serde_json::Value>(recorded), serde_json::from_str::<serde_json::Value>(body)) {
+                (Ok(recorded), Ok(body)) => recorded == body,
+                _ => recorded == body,
+            }
+        }
+        (recorded, body) => recorded == body,
+    }
+}
+
+/// Rebuild a response from its recording.
+fn response_from(recorded: &RecordedResponse) -> Result<HttpResponse, TodoistAPIError> {
+    let invalid = |what: &str| TodoistAPIError::TransportError(format!("Invalid {} in cassette", what));
+    let mut headers = HeaderMap::new();
+    for (name, value) in &recorded.headers {
+        headers.append(
+            HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid("header name"))?,
+            HeaderValue::from_str(value).map_err(|_| invalid("header value"))?,
+        );
+    }
+    Ok(HttpResponse {
+        status: StatusCode::from_u16(recorded.status).map_err(|_| invalid("status"))?,
+        headers,
+        body: recorded.body.clone().into_bytes(),
+    })
+}
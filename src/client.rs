@@ -0,0 +1,691 @@
+//! The HTTP client which every API call in this crate is made through.
+//!
+//! A `TodoistClient` holds the user's API token along with a single pooled HTTP client, so that
+//! connections (and their TLS sessions) are reused between calls rather than being set up afresh
+//! for every request. It also holds the base URL of the API server, which defaults to Todoist's
+//! own servers but can be pointed elsewhere (e.g. a local stand-in server for testing).
+//!
+//! The simplest way to get a client is `TodoistClient::new(<api_token>)`, which uses the default
+//! settings. To change the base URL, timeouts, proxy, user agent, retry policy or rate limiting, use
+//! `TodoistClient::builder(<api_token>)` instead:
+//! ```no_run
+//! use std::time::Duration;
+//! use todoist_v2_rest::TodoistClient;
+//! let client = TodoistClient::builder("a2a72c2f394b265bb798d5dc4ef55be51443d519")
+//!     .base_url("http://localhost:8080")
+//!     .timeout(Duration::from_secs(10))
+//!     .build()
+//!     .expect("Couldn't build client");
+//! ```
+//!
+//! Cloning a client is cheap, and clones share the same connection pool.
+//!
+//! # Request IDs
+//!
+//! Every request which changes something (creating, updating, closing or deleting an object) is
+//! sent with an `X-Request-Id` header holding a newly generated UUID. The same ID is used if the
+//! request is retried, so Todoist will discard the duplicate if an earlier attempt actually
+//! succeeded, and a network problem can't lead to e.g. a task being created twice.
+//!
+//! If you need to choose the ID yourself (for instance to safely repeat a call after your own
+//! process has restarted), use `with_request_id()` to get a copy of the client which sends that ID
+//! instead. Todoist ignores any request with an ID it has already seen, so use each such copy for
+//! a single call only. Uploading a file and then posting a comment with it, for example, is two
+//! calls, and needs two IDs.
+//!
+//! A call which sends several mutating requests, such as `CommandBatch::submit()` with more than
+//! `COMMAND_LIMIT` commands, sends the pinned ID with the first, and the pinned ID followed by `-`
+//! and the request's number with each of the others, so repeating the whole call is still safe.
+//! Reads through the Sync API (e.g. by `reminders::NewReminder::upload()` after adding the
+//! reminder) are never sent with an ID, and commands sent through the Sync API are given UUIDs
+//! derived from the pinned ID, so Todoist's reply to a repeated call still refers to them.
+//! ```no_run
+//! use todoist_v2_rest::{TodoistClient, tasks};
+//! let client = TodoistClient::new("a2a72c2f394b265bb798d5dc4ef55be51443d519");
+//! let new_task = tasks::NewTask::new("Buy milk");
+//! let task = new_task.upload(&client.with_request_id("8f1e5d4c-buy-milk"))
+//!     .expect("Couldn't create task");
+//! ```
+//!
+//! With the `async` feature enabled, the same builder can instead produce an `AsyncTodoistClient`
+//! using `build_async()`, which is used with the `_async` versions of the crate's functions.
+//!
+//! # Tracing
+//!
+//! With the `tracing` feature enabled, every request is made inside a `todoist_request` span at
+//! the `DEBUG` level, which records:
+//! - `endpoint`: the path of the request, with the object's ID replaced by `{id}` (e.g.
+//!   `/rest/v2/tasks/{id}/close`)
+//! - `method`: the HTTP method
+//! - `status`: the HTTP status of the final response, if there was one
+//! - `latency_ms`: the time taken, including any retries and waiting for the rate limiter
+//! - `retries`: how many times the request was retried
+//! - `response_size`: the length in bytes of the final response body
+//!
+//! An event is logged in the span once the request has finished (at `DEBUG` level if it
+//! succeeded, or `WARN` if not). Headers are never recorded, so the API token in the
+//! `Authorization` header can't end up in the logs.
+//!
+//! Requests are sent through an `HttpTransport`, which can be replaced (e.g. with a
+//! `MockTransport` in tests) using the builder's `transport()` method; see the `transport` module.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::{self, Config};
+use crate::err::TodoistAPIError;
+use crate::general::{error_for_status, ApiRequest, Body, Part, REDACTED};
+use crate::rate_limit::RateLimiter;
+use crate::retry::{Attempt, RetryPolicy};
+use crate::transport::{HttpRequest, HttpResponse, HttpTransport, ReqwestTransport};
+#[cfg(feature = "async")]
+use crate::transport::{AsyncHttpTransport, AsyncReqwestTransport};
+
+/// The base URL of Todoist's API servers, used unless another is given to the builder.
+pub const DEFAULT_BASE_URL: &str = "https://api.todoist.com";
+
+/// The user agent sent with every request, unless another is given to the builder.
+pub const DEFAULT_USER_AGENT: &str = concat!("todoist-v2-rest/", env!("CARGO_PKG_VERSION"));
+
+
+/// A client for the Todoist API, holding the user's API token and a pooled HTTP client.
+///
+/// Create an instance with `TodoistClient::new(<api_token>)`, or with
+/// `TodoistClient::builder(<api_token>)` to change any of the settings. A reference to a client
+/// must be passed to any function which makes an API request.
+#[derive(Clone)]
+pub struct TodoistClient {
+    config: ClientConfig,
+    transport: Arc<dyn HttpTransport>,
+}
+
+impl TodoistClient {
+    /// Create a new client with the default settings, passing in your API token.
+    pub fn new(api_token: &str) -> TodoistClient {
+        TodoistClient::builder(api_token)
+            .build()
+            .expect("Unable to initialise the HTTP client")
+    }
+
+    /// Start building a client with non-default settings, passing in your API token.
+    pub fn builder(api_token: &str) -> TodoistClientBuilder {
+        TodoistClientBuilder::new(api_token)
+    }
+
+    /// Create a client with the API token in the `TODOIST_API_TOKEN` environment variable, and
+    /// the base URL in `TODOIST_BASE_URL` if that is set. See the `config` module.
+    pub fn from_env() -> Result<TodoistClient, TodoistAPIError> {
+        TodoistClientBuilder::from_env()?.build()
+    }
+
+    /// Create a client with the token and settings of the named profile in the config file. See
+    /// the `config` module.
+    pub fn from_profile(name: &str) -> Result<TodoistClient, TodoistAPIError> {
+        Config::load()?.user(Some(name))
+    }
+
+    /// The base URL that requests are sent to.
+    pub fn base_url(&self) -> &str {
+        &self.config.base_url
+    }
+
+    /// The rate limiter which requests wait for, if one was given to the builder. Use this to see
+    /// how much of the request budget remains.
+    pub fn rate_limiter(&self) -> Option<&RateLimiter> {
+        self.config.rate_limiter.as_ref()
+    }
+
+    /// The request ID pinned with `with_request_id()`, if any.
+    pub(crate) fn pinned_request_id(&self) -> Option<&str> {
+        self.config.request_id.as_deref()
+    }
+
+    /// Return a copy of this client which sends the given request ID with the mutating request of
+    /// a single call, instead of generating a new one. See the `client` module for details.
+    pub fn with_request_id(&self, request_id: &str) -> Self {
+        Self {
+            config: self.config.with_request_id(request_id),
+            ..self.clone()
+        }
+    }
+
+    /// Return a copy of this client which uses a different API token, sharing the same settings
+    /// and connection pool. Useful for acting on behalf of several users, e.g. ones who have
+    /// connected their accounts through OAuth (see the `oauth` module).
+    pub fn with_token(&self, api_token: &str) -> Self {
+        Self {
+            config: self.config.with_token(api_token),
+            ..self.clone()
+        }
+    }
+
+    /// Send the request to the API, authenticated with this client's token, retrying according
+    /// to the client's `RetryPolicy`. Returns an error for an unsuccessful status.
+    pub(crate) fn send(&self, request: ApiRequest) -> Result<HttpResponse, TodoistAPIError> {
+        let trace = RequestTrace::start(&request);
+        trace.in_scope(|| {
+            let http_request = self.config.http_request(&request)?;
+            let mut attempt = 1;
+            let result = loop {
+                if let Some(limiter) = &self.config.rate_limiter {
+                    limiter.acquire();
+                }
+                let result = self.transport.send(&http_request);
+                match self.config.retry_delay(attempt, &request, &result) {
+                    Some(delay) => std::thread::sleep(delay),
+                    None => break result,
+                }
+                attempt += 1;
+            };
+            trace.finish(attempt, &result);
+            check_status(&request, &http_request, result?)
+        })
+    }
+}
+
+
+/// The asynchronous counterpart of `TodoistClient`, for use with the `_async` versions of the
+/// crate's functions. Only available with the `async` feature.
+///
+/// Create an instance with `AsyncTodoistClient::new(<api_token>)`, or with
+/// `TodoistClient::builder(<api_token>)...build_async()` to change any of the settings.
+#[cfg(feature = "async")]
+#[derive(Clone)]
+pub struct AsyncTodoistClient {
+    config: ClientConfig,
+    transport: Arc<dyn AsyncHttpTransport>,
+}
+
+#[cfg(feature = "async")]
+impl AsyncTodoistClient {
+    /// Create a new asynchronous client with the default settings, passing in your API token.
+    pub fn new(api_token: &str) -> AsyncTodoistClient {
+        TodoistClient::builder(api_token)
+            .build_async()
+            .expect("Unable to initialise the HTTP client")
+    }
+
+    /// Asynchronous version of `TodoistClient::from_env()`.
+    pub fn from_env() -> Result<AsyncTodoistClient, TodoistAPIError> {
+        TodoistClientBuilder::from_env()?.build_async()
+    }
+
+    /// Asynchronous version of `TodoistClient::from_profile()`.
+    pub fn from_profile(name: &str) -> Result<AsyncTodoistClient, TodoistAPIError> {
+        Config::load()?.profile(Some(name))?.builder()?.build_async()
+    }
+
+    /// The base URL that requests are sent to.
+    pub fn base_url(&self) -> &str {
+        &self.config.base_url
+    }
+
+    /// The rate limiter which requests wait for, if one was given to the builder. Use this to see
+    /// how much of the request budget remains.
+    pub fn rate_limiter(&self) -> Option<&RateLimiter> {
+        self.config.rate_limiter.as_ref()
+    }
+
+    /// The request ID pinned with `with_request_id()`, if any.
+    pub(crate) fn pinned_request_id(&self) -> Option<&str> {
+        self.config.request_id.as_deref()
+    }
+
+    /// Return a copy of this client which sends the given request ID with the mutating request of
+    /// a single call, instead of generating a new one. See the `client` module for details.
+    pub fn with_request_id(&self, request_id: &str) -> Self {
+        Self {
+            config: self.config.with_request_id(request_id),
+            ..self.clone()
+        }
+    }
+
+    /// Return a copy of this client which uses a different API token, sharing the same settings
+    /// and connection pool. Useful for acting on behalf of several users, e.g. ones who have
+    /// connected their accounts through OAuth (see the `oauth` module).
+    pub fn with_token(&self, api_token: &str) -> Self {
+        Self {
+            config: self.config.with_token(api_token),
+            ..self.clone()
+        }
+    }
+
+    /// Send the request to the API, authenticated with this client's token, retrying according
+    /// to the client's `RetryPolicy`. Returns an error for an unsuccessful status.
+    pub(crate) async fn send(&self, request: ApiRequest) -> Result<HttpResponse, TodoistAPIError> {
+        let trace = RequestTrace::start(&request);
+        trace.instrument(async {
+            let http_request = self.config.http_request(&request)?;
+            let mut attempt = 1;
+            let result = loop {
+                if let Some(limiter) = &self.config.rate_limiter {
+                    limiter.acquire_async().await;
+                }
+                let result = self.transport.send(&http_request).await;
+                match self.config.retry_delay(attempt, &request, &result) {
+                    Some(delay) => tokio::time::sleep(delay).await,
+                    None => break result,
+                }
+                attempt += 1;
+            };
+            trace.finish(attempt, &result);
+            check_status(&request, &http_request, result?)
+        }).await
+    }
+}
+
+
+impl fmt::Debug for TodoistClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TodoistClient")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "async")]
+impl fmt::Debug for AsyncTodoistClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncTodoistClient")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+
+/// The settings shared by the blocking and asynchronous clients.
+#[derive(Clone)]
+struct ClientConfig {
+    token: String,
+    base_url: String,
+    user_agent: String,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<RateLimiter>,
+    request_id: Option<String>,
+}
+
+impl fmt::Debug for ClientConfig {
+    /// Show the settings, except for the token.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientConfig")
+            .field("token", &REDACTED)
+            .field("base_url", &self.base_url)
+            .field("user_agent", &self.user_agent)
+            .field("retry_policy", &self.retry_policy)
+            .field("rate_limiter", &self.rate_limiter)
+            .field("request_id", &self.request_id)
+            .finish()
+    }
+}
+
+impl ClientConfig {
+    /// A copy of the settings with the given request ID pinned.
+    fn with_request_id(&self, request_id: &str) -> ClientConfig {
+        ClientConfig {
+            request_id: Some(request_id.to_string()),
+            ..self.clone()
+        }
+    }
+
+    /// A copy of the settings with a different API token.
+    fn with_token(&self, api_token: &str) -> ClientConfig {
+        ClientConfig {
+            token: api_token.to_string(),
+            ..self.clone()
+        }
+    }
+
+    /// Turn the request description into the full HTTP request to hand to the transport.
+    fn http_request(&self, request: &ApiRequest) -> Result<HttpRequest, TodoistAPIError> {
+        let base_url = request.base_url.as_deref().unwrap_or(&self.base_url);
+        let mut url = reqwest::Url::parse(&format!("{}{}", base_url, request.path))
+            .map_err(|err| TodoistAPIError::InvalidUrl(err.to_string()))?;
+        if !request.query.is_empty() {
+            url.query_pairs_mut().extend_pairs(&request.query);
+        }
+
+        let mut headers = vec![("User-Agent".to_string(), self.user_agent.clone())];
+        if request.authenticated {
+            headers.insert(0, ("Authorization".to_string(), format!("Bearer {}", self.token)));
+        }
+        if let Some(request_id) = &request.request_id {
+            let request_id = match (&self.request_id, &request.part) {
+                (Some(pinned), Some(part)) => format!("{}-{}", pinned, part),
+                (Some(pinned), None) => pinned.clone(),
+                (None, _) => request_id.clone(),
+            };
+            headers.push(("X-Request-Id".to_string(), request_id));
+        }
+
+        let body = match &request.body {
+            Some(Body::Json(value)) => {
+                headers.push(("Content-Type".to_string(), "application/json".to_string()));
+                Some(serde_json::to_vec(value).map_err(TodoistAPIError::SerdeSerialisationError)?)
+            }
+            Some(Body::Form(fields)) => {
+                headers.push(("Content-Type".to_string(), "application/x-www-form-urlencoded".to_string()));
+                Some(form_urlencoded(fields).into_bytes())
+            }
+            Some(Body::Multipart(parts)) => {
+                let (boundary, body) = multipart(parts);
+                headers.push(("Content-Type".to_string(), format!("multipart/form-data; boundary={}", boundary)));
+                Some(body)
+            }
+            None => None,
+        };
+
+        Ok(HttpRequest {
+            method: request.method.clone(),
+            url: url.to_string(),
+            headers,
+            body,
+        })
+    }
+
+    /// Decide whether to retry after the given attempt, and if so how long to wait first.
+    fn retry_delay(
+        &self,
+        attempt: u32,
+        request: &ApiRequest,
+        result: &Result<HttpResponse, TodoistAPIError>,
+    ) -> Option<Duration> {
+        let outcome = match result {
+            Ok(response) => Attempt::Responded(response.status, &response.headers),
+            Err(_) => Attempt::Failed,
+        };
+        self.retry_policy.retry_delay(attempt, request.is_repeatable(), outcome)
+    }
+}
+
+
+/// The `tracing` span of a single request, which records nothing unless the `tracing` feature is
+/// enabled. See the module documentation for the fields recorded.
+struct RequestTrace {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+    #[cfg(feature = "tracing")]
+    start: std::time::Instant,
+}
+
+impl RequestTrace {
+    /// Open the span for the request.
+    #[cfg(feature = "tracing")]
+    fn start(request: &ApiRequest) -> RequestTrace {
+        let span = tracing::debug_span!(
+            "todoist_request",
+            endpoint = %request.endpoint(),
+            method = %request.method,
+            status = tracing::field::Empty,
+            latency_ms = tracing::field::Empty,
+            retries = tracing::field::Empty,
+            response_size = tracing::field::Empty,
+        );
+        RequestTrace { span, start: std::time::Instant::now() }
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    fn start(_request: &ApiRequest) -> RequestTrace {
+        RequestTrace {}
+    }
+
+    /// Run the function inside the span.
+    fn in_scope<T>(&self, f: impl FnOnce() -> T) -> T {
+        #[cfg(feature = "tracing")]
+        let _entered = self.span.enter();
+        f()
+    }
+
+    /// Run the future inside the span.
+    #[cfg(feature = "async")]
+    async fn instrument<T>(&self, future: impl std::future::Future<Output = T>) -> T {
+        #[cfg(feature = "tracing")]
+        let future = tracing::Instrument::instrument(future, self.span.clone());
+        future.await
+    }
+
+    /// Record the outcome of the final attempt, which was attempt number `attempts`.
+    #[cfg(feature = "tracing")]
+    fn finish(&self, attempts: u32, result: &Result<HttpResponse, TodoistAPIError>) {
+        self.span.record("latency_ms", self.start.elapsed().as_millis() as u64);
+        self.span.record("retries", attempts - 1);
+        match result {
+            Ok(response) => {
+                self.span.record("status", response.status.as_u16());
+                self.span.record("response_size", response.body.len());
+                if response.status.is_success() {
+                    tracing::debug!("Todoist request succeeded");
+                } else {
+                    tracing::warn!("Todoist request failed with status {}", response.status);
+                }
+            }
+            Err(err) => tracing::warn!(error = %err, "Todoist request failed"),
+        }
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    fn finish(&self, _attempts: u32, _result: &Result<HttpResponse, TodoistAPIError>) {}
+}
+
+
+/// Encode the fields as an `application/x-www-form-urlencoded` body, which uses the same encoding
+/// as a URL's query string.
+pub(crate) fn form_urlencoded(fields: &[(String, String)]) -> String {
+    let mut url = reqwest::Url::parse("http://localhost/").expect("Constant URL is valid");
+    url.query_pairs_mut().extend_pairs(fields);
+    url.query().unwrap_or_default().to_string()
+}
+
+/// Encode the parts of a multipart body, returning the boundary between them and the body. The
+/// boundary is always the same unless a part happens to contain it, so that the same request is
+/// encoded the same way each time (e.g. to match a cassette).
+fn multipart(parts: &[Part]) -> (String, Vec<u8>) {
+    let contains = |data: &[u8], boundary: &str| data.windows(boundary.len()).any(|window| window == boundary.as_bytes());
+    let mut boundary = "todoist-v2-rest-boundary".to_string();
+    let mut suffix = 0;
+    while parts.iter().any(|part| contains(&part.data, &boundary)) {
+        suffix += 1;
+        boundary = format!("todoist-v2-rest-boundary-{}", suffix);
+    }
+
+    let mut body = Vec::new();
+    for part in parts {
+        body.extend_from_slice(format!("--{}\r\n", boundary).as_bytes());
+        match &part.file_name {
+            Some(file_name) => body.extend_from_slice(format!(
+                "Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: application/octet-stream\r\n\r\n",
+                part.name, file_name.replace('"', "%22"),
+            ).as_bytes()),
+            None => body.extend_from_slice(format!(
+                "Content-Disposition: form-data; name=\"{}\"\r\n\r\n", part.name,
+            ).as_bytes()),
+        }
+        body.extend_from_slice(&part.data);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+    (boundary, body)
+}
+
+
+/// Turn an unsuccessful status into the appropriate error, or pass a successful response through.
+fn check_status(
+    request: &ApiRequest,
+    http_request: &HttpRequest,
+    response: HttpResponse,
+) -> Result<HttpResponse, TodoistAPIError> {
+    if response.status.is_success() {
+        return Ok(response);
+    }
+    let message = response.text();
+    Err(error_for_status(request, http_request.url.clone(), response.status, &response.headers, message))
+}
+
+
+/// Builds a `TodoistClient` with non-default settings.
+///
+/// Get one of these from `TodoistClient::builder()`, set whichever options you need, then call
+/// `build()` (or `build_async()` for an `AsyncTodoistClient`).
+pub struct TodoistClientBuilder {
+    token: String,
+    base_url: String,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    proxy: Option<reqwest::Proxy>,
+    user_agent: String,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<RateLimiter>,
+    transport: Option<Arc<dyn HttpTransport>>,
+    #[cfg(feature = "async")]
+    async_transport: Option<Arc<dyn AsyncHttpTransport>>,
+}
+
+impl fmt::Debug for TodoistClientBuilder {
+    /// Show the settings, except for the token.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TodoistClientBuilder")
+            .field("token", &REDACTED)
+            .field("base_url", &self.base_url)
+            .field("timeout", &self.timeout)
+            .field("connect_timeout", &self.connect_timeout)
+            .field("user_agent", &self.user_agent)
+            .field("retry_policy", &self.retry_policy)
+            .field("rate_limiter", &self.rate_limiter)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TodoistClientBuilder {
+    /// Start building a client with the given API token and all other settings at their defaults.
+    pub fn new(api_token: &str) -> TodoistClientBuilder {
+        TodoistClientBuilder {
+            token: api_token.to_string(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            timeout: Some(Duration::from_secs(30)),
+            connect_timeout: None,
+            proxy: None,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            retry_policy: RetryPolicy::default(),
+            rate_limiter: None,
+            transport: None,
+            #[cfg(feature = "async")]
+            async_transport: None,
+        }
+    }
+
+    /// Start building a client with the API token in the `TODOIST_API_TOKEN` environment
+    /// variable, and the base URL in `TODOIST_BASE_URL` if that is set.
+    pub fn from_env() -> Result<TodoistClientBuilder, TodoistAPIError> {
+        config::builder_from_env()
+    }
+
+    /// Send requests to this base URL instead of Todoist's servers. This should be the scheme and
+    /// host (and optionally port) only, e.g. `http://localhost:8080`; REST requests will be sent
+    /// to `<base_url>/rest/v2/...`.
+    pub fn base_url(mut self, base_url: &str) -> TodoistClientBuilder {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// The total time allowed for each request, from connecting until the response body has been
+    /// read. The default is 30 seconds. Ignored if a custom transport is given.
+    pub fn timeout(mut self, timeout: Duration) -> TodoistClientBuilder {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// The time allowed for establishing a connection to the server. By default there is no
+    /// timeout. Ignored if a custom transport is given.
+    pub fn connect_timeout(mut self, timeout: Duration) -> TodoistClientBuilder {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Send all requests through the given proxy. Ignored if a custom transport is given.
+    pub fn proxy(mut self, proxy: reqwest::Proxy) -> TodoistClientBuilder {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Send this user agent with every request, instead of `DEFAULT_USER_AGENT`.
+    pub fn user_agent(mut self, user_agent: &str) -> TodoistClientBuilder {
+        self.user_agent = user_agent.to_string();
+        self
+    }
+
+    /// Retry failed requests according to this policy, instead of `RetryPolicy::default()`. Use
+    /// `RetryPolicy::none()` to disable retrying. See the `retry` module for details.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> TodoistClientBuilder {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Make every request (including each retry) wait for budget from this rate limiter before it
+    /// is sent. By default there is no rate limiting. See the `rate_limit` module for details.
+    pub fn rate_limiter(mut self, rate_limiter: RateLimiter) -> TodoistClientBuilder {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Send requests through this transport instead of a `ReqwestTransport`. See the `transport`
+    /// module for details.
+    pub fn transport<T: HttpTransport + 'static>(mut self, transport: T) -> TodoistClientBuilder {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Send requests from an `AsyncTodoistClient` through this transport instead of an
+    /// `AsyncReqwestTransport`. Only available with the `async` feature.
+    #[cfg(feature = "async")]
+    pub fn async_transport<T: AsyncHttpTransport + 'static>(mut self, transport: T) -> TodoistClientBuilder {
+        self.async_transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Build the client. This can only fail if the base URL is invalid, or the underlying HTTP
+    /// client can't be initialised (e.g. because the TLS backend is unavailable).
+    pub fn build(self) -> Result<TodoistClient, TodoistAPIError> {
+        let transport = match self.transport.clone() {
+            Some(transport) => transport,
+            None => Arc::new(ReqwestTransport::with_settings(
+                self.timeout,
+                self.connect_timeout,
+                self.proxy.clone(),
+            )?),
+        };
+        Ok(TodoistClient {
+            config: self.config()?,
+            transport,
+        })
+    }
+
+    /// Build an asynchronous client. Only available with the `async` feature.
+    #[cfg(feature = "async")]
+    pub fn build_async(self) -> Result<AsyncTodoistClient, TodoistAPIError> {
+        let transport = match self.async_transport.clone() {
+            Some(transport) => transport,
+            None => Arc::new(AsyncReqwestTransport::with_settings(
+                self.timeout,
+                self.connect_timeout,
+                self.proxy.clone(),
+            )?),
+        };
+        Ok(AsyncTodoistClient {
+            config: self.config()?,
+            transport,
+        })
+    }
+
+    /// The settings shared by both kinds of client, checking the base URL is valid.
+    fn config(self) -> Result<ClientConfig, TodoistAPIError> {
+        reqwest::Url::parse(&self.base_url).map_err(|err| TodoistAPIError::InvalidUrl(err.to_string()))?;
+        Ok(ClientConfig {
+            token: self.token,
+            base_url: self.base_url,
+            user_agent: self.user_agent,
+            retry_policy: self.retry_policy,
+            rate_limiter: self.rate_limiter,
+            request_id: None,
+        })
+    }
+}
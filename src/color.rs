@@ -3,7 +3,7 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 /// The available colours for Todoist projects, labels and filters.
 ///
 /// Corresponds to the colours in <https://developer.todoist.com/guides/#colors>
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[allow(missing_docs)]
 pub enum Color {
     BerryRed,
@@ -84,6 +84,7 @@ impl Color {
     }
 
     /// Returns the Color corresponding to the string
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Color> {
         match s {
             "berry_red" => Some(Color::BerryRed),
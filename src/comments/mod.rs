@@ -9,81 +9,146 @@
 //! * There is no `UpdateComment` struct, as once a comment has been made the only thing that
 //!   can be changed is its content.
 //!
-//! Comments downloaded from the API can contain an attachment, but there is no way to upload
-//! an attachment to Todoist's servers using the REST API.
+//! Comments can also have an attachment. To attach a local file, first upload it with
+//! `upload_file()`, then set the `Attachment` it returns as the new comment's `attachment`:
+//! ```no_run
+//! use todoist_v2_rest::{TodoistUser, comments};
+//! let user = TodoistUser::new("a2a72c2f394b265bb798d5dc4ef55be51443d519");
+//! let mut comment = comments::NewComment::on_task("2995104339", "Here's the receipt");
+//! comment.attachment = Some(comments::upload_file(&user, "receipt.pdf").expect("Couldn't upload"));
+//! comment.upload(&user).expect("Couldn't create comment");
+//! ```
 
 
 mod structs;
 
 pub use crate::comments::structs::comment::Comment;
-pub use crate::comments::structs::attachment::{self, Attachment};
+pub use crate::comments::structs::attachment::{self, Attachment, AttachmentType, Thumbnail, UploadState};
+pub use crate::comments::structs::new_comment::{CommentTarget, NewComment};
 use crate::err::TodoistAPIError;
-use crate::general::{get_204_from_reqwest_response, get_from_reqwest_response};
-
+use crate::general::{get_204_from_response, get_from_response, ApiRequest, Part};
 use crate::TodoistUser;
+#[cfg(feature = "async")]
+use crate::AsyncTodoistClient;
+use serde_json::json;
+use std::path::Path;
 
 
 /// Get all the comments attached to the given project
 pub fn get_comments_in_project(user: &TodoistUser, project_id: &str) -> Result<Vec<Comment>, TodoistAPIError> {
     // Send the API request
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .get("https://api.todoist.com/rest/v2/comments")
-        .header("Authorization", String::from("Bearer ") + &user.token)
-        .query(&[("project_id", project_id)])
-        .send();
-    get_from_reqwest_response(response)
+    let response = user.send(ApiRequest::get("/comments").query("project_id", project_id));
+    get_from_response(response)
+}
+
+/// Asynchronous version of `get_comments_in_project()`.
+#[cfg(feature = "async")]
+pub async fn get_comments_in_project_async(client: &AsyncTodoistClient, project_id: &str) -> Result<Vec<Comment>, TodoistAPIError> {
+    let response = client.send(ApiRequest::get("/comments").query("project_id", project_id)).await;
+    get_from_response(response)
 }
 
 
 /// Get all the comments attached to the given task
 pub fn get_comments_in_task(user: &TodoistUser, task_id: &str) -> Result<Vec<Comment>, TodoistAPIError> {
     // Send the API request
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .get("https://api.todoist.com/rest/v2/comments")
-        .header("Authorization", String::from("Bearer ") + &user.token)
-        .query(&[("task_id", task_id)])
-        .send();
-    get_from_reqwest_response(response)
+    let response = user.send(ApiRequest::get("/comments").query("task_id", task_id));
+    get_from_response(response)
+}
+
+/// Asynchronous version of `get_comments_in_task()`.
+#[cfg(feature = "async")]
+pub async fn get_comments_in_task_async(client: &AsyncTodoistClient, task_id: &str) -> Result<Vec<Comment>, TodoistAPIError> {
+    let response = client.send(ApiRequest::get("/comments").query("task_id", task_id)).await;
+    get_from_response(response)
 }
 
 /// Get a single comment by its ID
 pub fn get_comment_by_id(user: &TodoistUser, comment_id: &str) -> Result<Comment, TodoistAPIError> {
     // Send the API request
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .get(format!("https://api.todoist.com/rest/v2/comment/{}", comment_id))
-        .header("Authorization", String::from("Bearer ") + &user.token)
-        .send();
-    get_from_reqwest_response(response)
+    let request = ApiRequest::get(&format!("/comments/{}", comment_id))
+        .resource("comment", comment_id);
+    let response = user.send(request);
+    get_from_response(response)
+}
+
+/// Asynchronous version of `get_comment_by_id()`.
+#[cfg(feature = "async")]
+pub async fn get_comment_by_id_async(client: &AsyncTodoistClient, comment_id: &str) -> Result<Comment, TodoistAPIError> {
+    let request = ApiRequest::get(&format!("/comments/{}", comment_id))
+        .resource("comment", comment_id);
+    let response = client.send(request).await;
+    get_from_response(response)
 }
 
 /// Delete a comment with the given ID
 pub fn delete_comment_by_id(user: &TodoistUser, comment_id: &str) -> Result<(), TodoistAPIError> {
     // Send the API request
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .delete(format!("https://api.todoist.com/rest/v2/comments/{}", comment_id))
-        .header("Authorization", String::from("Bearer ") + &user.token)
-        .send();
-    get_204_from_reqwest_response(response)
+    let request = ApiRequest::delete(&format!("/comments/{}", comment_id))
+        .resource("comment", comment_id);
+    let response = user.send(request);
+    get_204_from_response(response)
+}
+
+/// Asynchronous version of `delete_comment_by_id()`.
+#[cfg(feature = "async")]
+pub async fn delete_comment_by_id_async(client: &AsyncTodoistClient, comment_id: &str) -> Result<(), TodoistAPIError> {
+    let request = ApiRequest::delete(&format!("/comments/{}", comment_id))
+        .resource("comment", comment_id);
+    let response = client.send(request).await;
+    get_204_from_response(response)
 }
 
 
 /// Update the text of the specified comment
 pub fn update_comment_content(user: &TodoistUser, comment_id: &str, new_content: &str) -> Result<Comment, TodoistAPIError> {
     // Create the JSON body
-    let body = format!("{{\"content\": \"{}\"}}", new_content);
+    let body = json!({"content": new_content});
+
+    // Send the API request
+    let request = ApiRequest::post(&format!("/comments/{}", comment_id))
+        .resource("comment", comment_id)
+        .json(&body)?;
+    let response = user.send(request);
+    get_from_response(response)
+}
+
+/// Asynchronous version of `update_comment_content()`.
+#[cfg(feature = "async")]
+pub async fn update_comment_content_async(client: &AsyncTodoistClient, comment_id: &str, new_content: &str) -> Result<Comment, TodoistAPIError> {
+    let body = json!({"content": new_content});
+    let request = ApiRequest::post(&format!("/comments/{}", comment_id))
+        .resource("comment", comment_id)
+        .json(&body)?;
+    let response = client.send(request).await;
+    get_from_response(response)
+}
+
+
+/// Upload a local file to Todoist, returning an `Attachment` for it to add to a `NewComment`.
+pub fn upload_file<P: AsRef<Path>>(user: &TodoistUser, path: P) -> Result<Attachment, TodoistAPIError> {
+    let data = std::fs::read(path.as_ref()).map_err(TodoistAPIError::FileError)?;
 
     // Send the API request
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .post(format!("https://api.todoist.com/rest/v2/comments/{}", comment_id))
-        .header("Authorization", String::from("Bearer ") + &user.token)
-        .header("Content-Type", "application/json")
-        .header("X-Request-Id", uuid::Uuid::new_v4().to_string())
-        .body(body)
-        .send();
-    get_from_reqwest_response(response)
+    let response = user.send(upload_file_request(path.as_ref(), data));
+    get_from_response(response)
+}
+
+/// Asynchronous version of `upload_file()`.
+#[cfg(feature = "async")]
+pub async fn upload_file_async<P: AsRef<Path>>(client: &AsyncTodoistClient, path: P) -> Result<Attachment, TodoistAPIError> {
+    let data = tokio::fs::read(path.as_ref()).await.map_err(TodoistAPIError::FileError)?;
+    let response = client.send(upload_file_request(path.as_ref(), data)).await;
+    get_from_response(response)
+}
+
+/// Build the request for `upload_file()` and its async version, given the file's contents.
+fn upload_file_request(path: &Path, data: Vec<u8>) -> ApiRequest {
+    let file_name = path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "file".to_string());
+    ApiRequest::sync_post("/uploads/add").multipart(vec![
+        Part { name: "file_name".to_string(), file_name: None, data: file_name.clone().into_bytes() },
+        Part { name: "file".to_string(), file_name: Some(file_name), data },
+    ])
 }
@@ -1,52 +1,86 @@
-use serde::{Deserialize};
+use serde::{Deserialize, Serialize};
 
-/// Defines an attachment to a comment. This can be returned from the API,
-/// and can also be used in the creation of a comment, although you can't
-/// actually upload new attachments to Todoist's servers using the REST API.
-#[derive(Debug, Deserialize)]
+/// Defines an attachment to a comment. This can be returned from the API, and can also be used
+/// in the creation of a comment. To attach a local file, upload it with `upload_file()` to get
+/// an attachment for it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Attachment {
-    file_name: String,
-    file_size: u64,
-    file_type: String,
-    file_url: String,
-    upload_state: Option<UploadState>,
+    /// The kind of attachment, which is "file" for every attachment this crate deals with.
+    #[serde(default = "default_resource_type")]
+    pub resource_type: String,
 
+    /// The name of the file.
+    pub file_name: String,
+
+    /// The size of the file in bytes.
+    pub file_size: u64,
+
+    /// The MIME type of the file, e.g. "image/png".
+    pub file_type: String,
+
+    /// The URL the file can be downloaded from.
+    pub file_url: String,
+
+    /// Whether the file has finished uploading, if Todoist said.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upload_state: Option<UploadState>,
+
+    /// Any extra information for particular kinds of file, e.g. an image's thumbnails.
     #[serde(flatten)]
     #[serde(default)]
-    attachment_type: AttachmentType,
+    pub attachment_type: AttachmentType,
 }
 
+fn default_resource_type() -> String {
+    "file".to_string()
+}
 
-#[derive(Debug, Deserialize)]
+
+/// Whether an attachment's file has finished uploading to Todoist's servers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum UploadState {
+    /// The file is still being uploaded.
     #[serde(rename = "pending")]
     Pending,
 
+    /// The file has been fully uploaded.
     #[serde(rename = "completed")]
     Completed
 }
 
 
-#[derive(Debug, Deserialize)]
+/// The extra information which comes with particular kinds of attachment.
+///
+/// Since the variants are told apart by which fields the attachment has, `Basic` must come last:
+/// it matches any attachment at all.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum AttachmentType {
-    Basic(),
+    /// An image, with large, medium and small thumbnails.
+    #[allow(missing_docs)]
     Image {
         tn_l: Thumbnail,
         tn_m: Thumbnail,
         tn_s: Thumbnail,
     },
+
+    /// An audio file, with its duration in seconds.
+    #[allow(missing_docs)]
     Audio {
         file_duration: i32
-    }
+    },
+
+    /// Any attachment without extra information.
+    Basic {},
 }
 
 // If no special attachment information is specified, then default to Basic
 // This means the attachment type isn't an Option
 impl Default for AttachmentType {
     fn default() -> Self {
-        AttachmentType::Basic()
+        AttachmentType::Basic {}
     }
 }
 
+/// A thumbnail of an image attachment, given as its URL, width and height.
 pub type Thumbnail = (String, u32, u32);
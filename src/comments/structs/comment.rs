@@ -1,15 +1,26 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::comments::structs::attachment::Attachment;
 
 /// Defines the `Comment` struct, which is returned from API calls to represent comments in
 /// Todoist.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Comment {
-    id: String,
-    task_id: Option<String>,
-    project_id: Option<String>,
-    posted_at: String,
-    content: String,
-    attachment: Option<Attachment>,
+    /// The ID of the comment.
+    pub id: String,
+
+    /// The ID of the task the comment is on, if it is on a task.
+    pub task_id: Option<String>,
+
+    /// The ID of the project the comment is on, if it is on a project.
+    pub project_id: Option<String>,
+
+    /// When the comment was posted, in RFC3339 format in UTC.
+    pub posted_at: String,
+
+    /// The text of the comment, which may use Markdown.
+    pub content: String,
+
+    /// The file attached to the comment, if there is one.
+    pub attachment: Option<Attachment>,
 }
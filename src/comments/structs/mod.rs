@@ -1,2 +1,8 @@
+/// Defines the `Comment` struct.
 pub mod comment;
+
+/// Defines the `Attachment` struct and the types used within it.
 pub mod attachment;
+
+/// Defines the `NewComment` struct and its `upload()` function.
+pub mod new_comment;
@@ -0,0 +1,68 @@
+use serde::Serialize;
+use crate::comments::{Attachment, Comment};
+use crate::err::TodoistAPIError;
+use crate::general::{get_from_response, ApiRequest};
+use crate::TodoistUser;
+#[cfg(feature = "async")]
+use crate::AsyncTodoistClient;
+
+/// Holds the information needed to create a new comment. Create one with `NewComment::on_task()`
+/// or `NewComment::on_project()`, optionally set `attachment`, then call `upload()`.
+#[derive(Debug, Serialize)]
+pub struct NewComment {
+    /// The task or project the comment is on.
+    #[serde(flatten)]
+    pub target: CommentTarget,
+
+    /// The text of the comment, which may use Markdown.
+    pub content: String,
+
+    /// A file to attach to the comment, e.g. from `upload_file()`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attachment: Option<Attachment>,
+}
+
+
+/// What a comment is attached to: either a task or a project.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+#[allow(missing_docs)]
+pub enum CommentTarget {
+    Task { task_id: String },
+    Project { project_id: String },
+}
+
+
+impl NewComment {
+    /// A new comment on the given task, with no attachment.
+    pub fn on_task(task_id: &str, content: &str) -> NewComment {
+        NewComment {
+            target: CommentTarget::Task { task_id: task_id.to_string() },
+            content: content.to_string(),
+            attachment: None,
+        }
+    }
+
+    /// A new comment on the given project, with no attachment.
+    pub fn on_project(project_id: &str, content: &str) -> NewComment {
+        NewComment {
+            target: CommentTarget::Project { project_id: project_id.to_string() },
+            content: content.to_string(),
+            attachment: None,
+        }
+    }
+
+    /// Create the comment, returning it as Todoist stored it.
+    pub fn upload(&self, user: &TodoistUser) -> Result<Comment, TodoistAPIError> {
+        // Send the API request
+        let response = user.send(ApiRequest::post("/comments").json(self)?);
+        get_from_response(response)
+    }
+
+    /// Asynchronous version of `upload()`.
+    #[cfg(feature = "async")]
+    pub async fn upload_async(&self, client: &AsyncTodoistClient) -> Result<Comment, TodoistAPIError> {
+        let response = client.send(ApiRequest::post("/comments").json(self)?).await;
+        get_from_response(response)
+    }
+}
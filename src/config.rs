@@ -0,0 +1,239 @@
+//! Loading the API token and client settings from the environment or a config file.
+//!
+//! For a single account, the simplest option is to set the `TODOIST_API_TOKEN` environment
+//! variable (and optionally `TODOIST_BASE_URL`) and use `TodoistUser::from_env()`:
+//! ```no_run
+//! use todoist_v2_rest::{TodoistUser, projects};
+//! let user = TodoistUser::from_env().expect("TODOIST_API_TOKEN isn't set");
+//! let user_projects = projects::get_projects(&user).expect("Couldn't load project list");
+//! ```
+//!
+//! For several accounts, use a TOML config file with a named profile for each one. The file is
+//! read from the path in the `TODOIST_CONFIG` environment variable if it is set, and otherwise
+//! from `todoist/config.toml` in the user's config directory (`$XDG_CONFIG_HOME`, or
+//! `~/.config`):
+//! ```toml
+//! # Used when no profile is named (and TODOIST_PROFILE isn't set)
+//! default_profile = "personal"
+//!
+//! [profiles.personal]
+//! token = "a2a72c2f394b265bb798d5dc4ef55be51443d519"
+//!
+//! [profiles.work]
+//! # Read the token from this environment variable, rather than keeping it in the file
+//! token_env = "WORK_TODOIST_TOKEN"
+//! base_url = "http://localhost:8080"
+//! timeout_secs = 10
+//! max_attempts = 5
+//! user_agent = "work-tools/1.0"
+//! default_project_id = "2203306141"
+//! ```
+//!
+//! Then get a user for a profile with `TodoistUser::from_profile()`, or load the `Config` to look
+//! at the profiles first:
+//! ```no_run
+//! use todoist_v2_rest::TodoistUser;
+//! use todoist_v2_rest::config::Config;
+//! let work = TodoistUser::from_profile("work").expect("Couldn't load work profile");
+//!
+//! let config = Config::load().expect("Couldn't load config file");
+//! let profile = config.profile(None).expect("No default profile");
+//! let user = profile.user().expect("Couldn't create client");
+//! println!("Default project: {:?}", profile.default_project_id);
+//! ```
+//!
+//! Neither `Profile` nor any client prints its token when formatted with `{:?}`, so they can be
+//! logged safely.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::err::TodoistAPIError;
+pub use crate::general::REDACTED;
+use crate::retry::RetryPolicy;
+use crate::{TodoistClient, TodoistClientBuilder, TodoistUser};
+
+/// The environment variable `from_env()` reads the API token from.
+pub const TOKEN_ENV_VAR: &str = "TODOIST_API_TOKEN";
+
+/// The environment variable `from_env()` reads the base URL from, if it is set.
+pub const BASE_URL_ENV_VAR: &str = "TODOIST_BASE_URL";
+
+/// The environment variable giving the path of the config file, if it is set.
+pub const CONFIG_ENV_VAR: &str = "TODOIST_CONFIG";
+
+/// The environment variable naming the profile to use when none is given, if it is set.
+pub const PROFILE_ENV_VAR: &str = "TODOIST_PROFILE";
+
+
+/// The contents of a config file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The profile to use when none is named and `TODOIST_PROFILE` isn't set. If this isn't given
+    /// either, the profile called "default" is used.
+    pub default_profile: Option<String>,
+
+    /// The profiles, by name.
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl Config {
+    /// Read the config file from its default location: the path in `TODOIST_CONFIG` if that is
+    /// set, or otherwise `todoist/config.toml` in the user's config directory.
+    pub fn load() -> Result<Config, TodoistAPIError> {
+        let path = Config::default_path()
+            .ok_or_else(|| config_error("couldn't find the config directory; set TODOIST_CONFIG"))?;
+        Config::from_file(path)
+    }
+
+    /// Read a config file from the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Config, TodoistAPIError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| config_error(&format!("couldn't read {}: {}", path.display(), err)))?;
+        Config::parse(&text)
+            .map_err(|err| config_error(&format!("couldn't parse {}: {}", path.display(), err)))
+    }
+
+    /// Parse the contents of a config file.
+    pub fn parse(text: &str) -> Result<Config, TodoistAPIError> {
+        toml::from_str(text).map_err(|err| config_error(&err.to_string()))
+    }
+
+    /// The default location of the config file, if there is one.
+    pub fn default_path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os(CONFIG_ENV_VAR) {
+            return Some(PathBuf::from(path));
+        }
+        let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config_dir.join("todoist").join("config.toml"))
+    }
+
+    /// The named profile. If no name is given, use the one named by `TODOIST_PROFILE`, then
+    /// `default_profile`, then the one called "default".
+    pub fn profile(&self, name: Option<&str>) -> Result<&Profile, TodoistAPIError> {
+        let name = match name {
+            Some(name) => name.to_string(),
+            None => std::env::var(PROFILE_ENV_VAR).ok()
+                .or_else(|| self.default_profile.clone())
+                .unwrap_or_else(|| "default".to_string()),
+        };
+        self.profiles.get(&name)
+            .ok_or_else(|| config_error(&format!("there is no profile called \"{}\"", name)))
+    }
+
+    /// A user for the named profile (or the default profile, as for `profile()`).
+    pub fn user(&self, name: Option<&str>) -> Result<TodoistUser, TodoistAPIError> {
+        self.profile(name)?.user()
+    }
+}
+
+
+/// The settings for one account in a config file. Everything except the token is optional.
+#[derive(Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// The API token.
+    pub token: Option<String>,
+
+    /// The environment variable to read the API token from, if `token` isn't given.
+    pub token_env: Option<String>,
+
+    /// The base URL to send requests to, instead of Todoist's servers.
+    pub base_url: Option<String>,
+
+    /// The total time allowed for each request, in seconds.
+    pub timeout_secs: Option<u64>,
+
+    /// The maximum number of times to send each request, including the first attempt.
+    pub max_attempts: Option<u32>,
+
+    /// The user agent to send with every request.
+    pub user_agent: Option<String>,
+
+    /// The project to use when a tool isn't told which one. This isn't used by the crate itself.
+    pub default_project_id: Option<String>,
+}
+
+impl Profile {
+    /// The profile's API token, from `token` or else the environment variable named by
+    /// `token_env`.
+    pub fn token(&self) -> Result<String, TodoistAPIError> {
+        if let Some(token) = &self.token {
+            return Ok(token.clone());
+        }
+        match &self.token_env {
+            Some(variable) => token_from_env(variable),
+            None => Err(config_error("the profile has neither token nor token_env")),
+        }
+    }
+
+    /// A client builder with the profile's token and settings, to change further settings before
+    /// building the client.
+    pub fn builder(&self) -> Result<TodoistClientBuilder, TodoistAPIError> {
+        let mut builder = TodoistClient::builder(&self.token()?);
+        if let Some(base_url) = &self.base_url {
+            builder = builder.base_url(base_url);
+        }
+        if let Some(timeout) = self.timeout_secs {
+            builder = builder.timeout(Duration::from_secs(timeout));
+        }
+        if let Some(max_attempts) = self.max_attempts {
+            builder = builder.retry_policy(RetryPolicy { max_attempts, ..RetryPolicy::default() });
+        }
+        if let Some(user_agent) = &self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        Ok(builder)
+    }
+
+    /// A user with the profile's token and settings.
+    pub fn user(&self) -> Result<TodoistUser, TodoistAPIError> {
+        self.builder()?.build()
+    }
+}
+
+impl fmt::Debug for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Profile")
+            .field("token", &self.token.as_ref().map(|_| REDACTED))
+            .field("token_env", &self.token_env)
+            .field("base_url", &self.base_url)
+            .field("timeout_secs", &self.timeout_secs)
+            .field("max_attempts", &self.max_attempts)
+            .field("user_agent", &self.user_agent)
+            .field("default_project_id", &self.default_project_id)
+            .finish()
+    }
+}
+
+
+/// A client builder with the token from `TODOIST_API_TOKEN`, and the base URL from
+/// `TODOIST_BASE_URL` if it is set.
+pub(crate) fn builder_from_env() -> Result<TodoistClientBuilder, TodoistAPIError> {
+    let mut builder = TodoistClient::builder(&token_from_env(TOKEN_ENV_VAR)?);
+    if let Ok(base_url) = std::env::var(BASE_URL_ENV_VAR) {
+        builder = builder.base_url(&base_url);
+    }
+    Ok(builder)
+}
+
+/// Read a token from the named environment variable, which must be set and non-empty.
+fn token_from_env(variable: &str) -> Result<String, TodoistAPIError> {
+    match std::env::var(variable) {
+        Ok(token) if !token.trim().is_empty() => Ok(token.trim().to_string()),
+        _ => Err(config_error(&format!("the environment variable {} isn't set", variable))),
+    }
+}
+
+fn config_error(message: &str) -> TodoistAPIError {
+    TodoistAPIError::ConfigError(message.to_string())
+}
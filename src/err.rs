@@ -1,9 +1,17 @@
+use std::fmt;
+use std::time::Duration;
 use reqwest;
+use reqwest::{Method, StatusCode};
 use serde_json;
 
 /// Holds the different kinds of things that can go wrong when making API requests.
 /// Functions in this crate will typically return a Result with this as the error type,
 /// so that it's possible for the caller to know what stage a problem happened at.
+///
+/// When Todoist responds with an unsuccessful HTTP status, the error is one of the variants from
+/// `Unauthorized` to `UnexpectedStatus`, depending on the status. Each of those holds the text of
+/// the response (which typically explains the issue) and a `RequestInfo` saying which request
+/// failed.
 #[derive(Debug)]
 pub enum TodoistAPIError {
     /// Indicates that the reqwest module either failed to send an HTTP request, or
@@ -18,26 +26,261 @@ pub enum TodoistAPIError {
     /// Holds a copy of the Error returned by reqwest.
     ReqwestRequestError(reqwest::Error),
 
-    /// A valid HTTP response was received, but the status code indicates an unsuccessful API
-    /// request.
-    ///
-    /// - If the status code is 4xx, then the request was invalid in some way (e.g. missing/bad
-    ///   authentication). It could also indicate an internal crate error, e.g. putting a bad
-    ///   header or value into the request.
-    /// - If the status code is 5xx, it's on Todoist's side and indicates a server issue.
-    ///
-    /// Holds the problematic status code, as well as the text of the response from the API (which
-    /// typically will explain the issue).
-    UnsuccessfulHTTPStatus(reqwest::StatusCode, String),
+    /// A custom transport (see the `transport` module) failed to get a response, for the reason
+    /// given.
+    TransportError(String),
+
+    /// Todoist responded with `400 Bad Request`, meaning the request was invalid in some way, e.g.
+    /// a field had a value the API doesn't accept.
+    BadRequest {
+        /// Todoist's explanation of the problem.
+        message: String,
+        /// The request which failed.
+        request: RequestInfo,
+    },
+
+    /// Todoist responded with `401 Unauthorized`, meaning the API token is missing, invalid or
+    /// has been revoked.
+    Unauthorized {
+        /// Todoist's explanation of the problem.
+        message: String,
+        /// The request which failed.
+        request: RequestInfo,
+    },
+
+    /// Todoist responded with `403 Forbidden`, meaning the token is valid but doesn't give access
+    /// to the object or action requested.
+    Forbidden {
+        /// Todoist's explanation of the problem.
+        message: String,
+        /// The request which failed.
+        request: RequestInfo,
+    },
+
+    /// Todoist responded with `404 Not Found`, which typically means there is no object with the
+    /// given ID (or it belongs to another user).
+    NotFound {
+        /// The kind of object that was asked for (e.g. "task"), or the path of the request if it
+        /// wasn't about a particular object.
+        resource: String,
+        /// The ID of the object that was asked for, if there was one.
+        id: Option<String>,
+        /// The request which failed.
+        request: RequestInfo,
+    },
+
+    /// Todoist responded with `429 Too Many Requests`, because the user's request quota has been
+    /// used up. This is only returned once the client's retry policy has given up retrying.
+    RateLimited {
+        /// How long Todoist asked us to wait before trying again, if it said.
+        retry_after: Option<Duration>,
+        /// The request which failed.
+        request: RequestInfo,
+    },
+
+    /// Todoist responded with a `5xx` status, indicating a problem on Todoist's side. This is only
+    /// returned once the client's retry policy has given up retrying.
+    ServerError {
+        /// The status Todoist responded with.
+        status: StatusCode,
+        /// The text of the response.
+        message: String,
+        /// The request which failed.
+        request: RequestInfo,
+    },
+
+    /// Todoist responded with some other unsuccessful status not covered by the variants above.
+    UnexpectedStatus {
+        /// The status Todoist responded with.
+        status: StatusCode,
+        /// The text of the response.
+        message: String,
+        /// The request which failed.
+        request: RequestInfo,
+    },
 
     /// An API response was received which should have contained a valid JSON response, but
     /// this could not be deserialised to the type of object expected. This could either be because
     /// Todoist is sending back malformed JSON, or (more likely) because the struct type the crate
     /// is trying to deserialise the JSON into is defined wrong (e.g. fails to account for a field
     /// being able to be `null`).
-    ReqwestDeserialisationError(reqwest::Error),
+    DeserialisationError(serde_json::Error),
 
     /// Failed to serialise a structure into JSON form. This most likely is caused by an error in
     /// the crate.
     SerdeSerialisationError(serde_json::Error),
+
+    /// The underlying HTTP client couldn't be created when building a `TodoistClient`, e.g.
+    /// because the TLS backend couldn't be initialised.
+    ClientBuildError(reqwest::Error),
+
+    /// The client's base URL, or a URL built from it, isn't a valid URL.
+    InvalidUrl(String),
+
+    /// A `RateLimiter` was created with a quota of zero requests, or a zero-length window, neither
+    /// of which would ever let a request through.
+    InvalidRateLimiter(String),
+
+    /// The API token or client settings couldn't be loaded from the environment or a config file
+    /// (see the `config` module), for the reason given.
+    ConfigError(String),
+
+    /// Todoist rejected a command sent through the Sync API, e.g. by `tasks::move_task()`.
+    CommandError(crate::sync::CommandError),
+
+    /// No collaborator on the project has the email address or name given, e.g. to
+    /// `tasks::assign_task_by_email()`.
+    CollaboratorNotFound {
+        /// The project whose collaborators were searched.
+        project_id: String,
+        /// The email address or name which was looked for.
+        person: String,
+    },
+
+    /// More than one collaborator on the project has the name given to
+    /// `tasks::assign_task_by_name()`, so it isn't clear who to assign the task to. Assign it by
+    /// email address instead.
+    AmbiguousCollaborator {
+        /// The project whose collaborators were searched.
+        project_id: String,
+        /// The name which was looked for.
+        name: String,
+        /// The IDs of the collaborators with that name.
+        ids: Vec<String>,
+    },
+
+    /// A local file couldn't be read, e.g. one given to `comments::upload_file()`.
+    FileError(std::io::Error),
+
+    /// Todoist accepted a command creating or updating an object through the Sync API, but the
+    /// object wasn't among those it returned afterwards, e.g. because it was deleted in the
+    /// meantime.
+    ObjectNotReturned {
+        /// The kind of object that was looked for (e.g. "reminder").
+        resource: String,
+        /// The ID of the object.
+        id: String,
+    },
+}
+
+impl TodoistAPIError {
+    /// Whether the same request might succeed if it is made again later: true for failures to get
+    /// a response at all, rate limiting and server errors.
+    ///
+    /// Note that the client will already have retried these according to its retry policy before
+    /// returning the error.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            TodoistAPIError::ReqwestRequestError(_)
+                | TodoistAPIError::TransportError(_)
+                | TodoistAPIError::RateLimited { .. }
+                | TodoistAPIError::ServerError { .. }
+        )
+    }
+
+    /// The request which failed, if the error came from an unsuccessful HTTP status.
+    pub fn request(&self) -> Option<&RequestInfo> {
+        match self {
+            TodoistAPIError::BadRequest { request, .. }
+            | TodoistAPIError::Unauthorized { request, .. }
+            | TodoistAPIError::Forbidden { request, .. }
+            | TodoistAPIError::NotFound { request, .. }
+            | TodoistAPIError::RateLimited { request, .. }
+            | TodoistAPIError::ServerError { request, .. }
+            | TodoistAPIError::UnexpectedStatus { request, .. } => Some(request),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TodoistAPIError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TodoistAPIError::ReqwestRequestError(err) => write!(f, "request to Todoist failed: {}", err),
+            TodoistAPIError::TransportError(message) => write!(f, "request to Todoist failed: {}", message),
+            TodoistAPIError::BadRequest { message, request } => {
+                write!(f, "bad request ({}): {}", request, message)
+            }
+            TodoistAPIError::Unauthorized { message, request } => {
+                write!(f, "unauthorized, check the API token ({}): {}", request, message)
+            }
+            TodoistAPIError::Forbidden { message, request } => {
+                write!(f, "forbidden ({}): {}", request, message)
+            }
+            TodoistAPIError::NotFound { resource, id: Some(id), request } => {
+                write!(f, "{} {} not found ({})", resource, id, request)
+            }
+            TodoistAPIError::NotFound { resource, id: None, request } => {
+                write!(f, "{} not found ({})", resource, request)
+            }
+            TodoistAPIError::RateLimited { retry_after: Some(retry_after), request } => {
+                write!(f, "rate limited, retry after {}s ({})", retry_after.as_secs(), request)
+            }
+            TodoistAPIError::RateLimited { retry_after: None, request } => {
+                write!(f, "rate limited ({})", request)
+            }
+            TodoistAPIError::ServerError { status, message, request } => {
+                write!(f, "Todoist server error {} ({}): {}", status, request, message)
+            }
+            TodoistAPIError::UnexpectedStatus { status, message, request } => {
+                write!(f, "unexpected status {} ({}): {}", status, request, message)
+            }
+            TodoistAPIError::DeserialisationError(err) => {
+                write!(f, "couldn't deserialise Todoist's response: {}", err)
+            }
+            TodoistAPIError::SerdeSerialisationError(err) => {
+                write!(f, "couldn't serialise the request: {}", err)
+            }
+            TodoistAPIError::ClientBuildError(err) => {
+                write!(f, "couldn't build the HTTP client: {}", err)
+            }
+            TodoistAPIError::InvalidUrl(message) => write!(f, "invalid URL: {}", message),
+            TodoistAPIError::InvalidRateLimiter(message) => write!(f, "invalid rate limiter: {}", message),
+            TodoistAPIError::ConfigError(message) => write!(f, "couldn't load Todoist settings: {}", message),
+            TodoistAPIError::CommandError(err) => {
+                write!(f, "Todoist rejected the command (error {}): {}", err.error_code, err.error)
+            }
+            TodoistAPIError::CollaboratorNotFound { project_id, person } => {
+                write!(f, "{} isn't a collaborator on project {}", person, project_id)
+            }
+            TodoistAPIError::AmbiguousCollaborator { project_id, name, ids } => {
+                write!(f, "more than one collaborator on project {} is called {} ({})", project_id, name, ids.join(", "))
+            }
+            TodoistAPIError::FileError(err) => write!(f, "couldn't read file: {}", err),
+            TodoistAPIError::ObjectNotReturned { resource, id } => {
+                write!(f, "Todoist didn't return {} {}", resource, id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TodoistAPIError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TodoistAPIError::ReqwestRequestError(err)
+            | TodoistAPIError::ClientBuildError(err) => Some(err),
+            TodoistAPIError::DeserialisationError(err)
+            | TodoistAPIError::SerdeSerialisationError(err) => Some(err),
+            TodoistAPIError::FileError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+
+/// Identifies the request which led to an error, for diagnostics.
+#[derive(Debug, Clone)]
+pub struct RequestInfo {
+    /// The HTTP method of the request.
+    pub method: Method,
+
+    /// The full URL the request was sent to, including any query string.
+    pub url: String,
+}
+
+impl fmt::Display for RequestInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.method, self.url)
+    }
 }
@@ -0,0 +1,132 @@
+//! Functions for working with saved filters.
+//!
+//! Filters are saved searches, such as `today | overdue` or `#Work & p1`. The REST API doesn't
+//! cover them, so these functions go through the Sync API instead. Filters can be downloaded
+//! (either all at once or by ID), created, updated, reordered and deleted:
+//!
+//! - To get all filters, use `get_all_filters()`
+//! - To get a filter by its ID, use `get_filter_by_id()`
+//! - To create a new filter, make a `NewFilter` struct and call `upload()` on it
+//! - To update a filter whose ID you know, make an `UpdateFilter` struct and call `update()` on it
+//! - To put filters in a new order, use `reorder_filters()` with their IDs in that order
+//! - To delete a filter whose ID you know, run `delete_filter_by_id()`
+//!
+//! The same changes can be made alongside others in a `sync::CommandBatch`.
+
+
+mod structs;
+
+pub use structs::filter::Filter;
+pub use structs::new_filter::NewFilter;
+pub use structs::update_filter::UpdateFilter;
+
+use serde::Deserialize;
+use crate::err::TodoistAPIError;
+use crate::general::{get_from_response, ApiRequest};
+use crate::sync::{self, CommandBatch};
+use crate::TodoistUser;
+#[cfg(feature = "async")]
+use crate::AsyncTodoistClient;
+
+
+/// Get a Vec of all the user's filters.
+pub fn get_all_filters(user: &TodoistUser) -> Result<Vec<Filter>, TodoistAPIError> {
+    // Send the API request
+    let response = user.send(sync::resource_request("filters"));
+    sync::resources_from_response(response, "filters")
+}
+
+/// Asynchronous version of `get_all_filters()`.
+#[cfg(feature = "async")]
+pub async fn get_all_filters_async(client: &AsyncTodoistClient) -> Result<Vec<Filter>, TodoistAPIError> {
+    let response = client.send(sync::resource_request("filters")).await;
+    sync::resources_from_response(response, "filters")
+}
+
+/// Get an individual Filter by its ID.
+pub fn get_filter_by_id(user: &TodoistUser, id: &str) -> Result<Filter, TodoistAPIError> {
+    // Send the API request
+    let response = user.send(filter_request(id));
+    get_from_response::<FilterResponse>(response).map(|response| response.filter)
+}
+
+/// Asynchronous version of `get_filter_by_id()`.
+#[cfg(feature = "async")]
+pub async fn get_filter_by_id_async(client: &AsyncTodoistClient, id: &str) -> Result<Filter, TodoistAPIError> {
+    let response = client.send(filter_request(id)).await;
+    get_from_response::<FilterResponse>(response).map(|response| response.filter)
+}
+
+
+/// Put the filters with the given IDs in that order, in the positions they take up between them.
+/// Filters which aren't given keep their current position.
+///
+/// Todoist needs the new position of every filter, so this downloads them all first.
+pub fn reorder_filters(user: &TodoistUser, ids: &[&str]) -> Result<(), TodoistAPIError> {
+    let order = full_order(get_all_filters(user)?, ids);
+    let mut batch = CommandBatch::new();
+    batch.reorder_filters(&order.iter().map(String::as_str).collect::<Vec<_>>());
+    batch.submit(user)?.into_result().map(|_| ())
+}
+
+/// Asynchronous version of `reorder_filters()`.
+#[cfg(feature = "async")]
+pub async fn reorder_filters_async(client: &AsyncTodoistClient, ids: &[&str]) -> Result<(), TodoistAPIError> {
+    let order = full_order(get_all_filters_async(client).await?, ids);
+    let mut batch = CommandBatch::new();
+    batch.reorder_filters(&order.iter().map(String::as_str).collect::<Vec<_>>());
+    batch.submit_async(client).await?.into_result().map(|_| ())
+}
+
+
+/// Delete the filter with the given ID.
+pub fn delete_filter_by_id(user: &TodoistUser, id: &str) -> Result<(), TodoistAPIError> {
+    let mut batch = CommandBatch::new();
+    batch.delete_filter(id);
+    batch.submit(user)?.into_result().map(|_| ())
+}
+
+/// Asynchronous version of `delete_filter_by_id()`.
+#[cfg(feature = "async")]
+pub async fn delete_filter_by_id_async(client: &AsyncTodoistClient, id: &str) -> Result<(), TodoistAPIError> {
+    let mut batch = CommandBatch::new();
+    batch.delete_filter(id);
+    batch.submit_async(client).await?.into_result().map(|_| ())
+}
+
+
+/// The response to a request for a single filter.
+#[derive(Deserialize)]
+struct FilterResponse {
+    filter: Filter,
+}
+
+/// The IDs of all the filters in their new order, once those with the given IDs have been put in
+/// that order. Any given IDs which don't belong to a filter go at the end, for Todoist to reject.
+fn full_order(mut filters: Vec<Filter>, ids: &[&str]) -> Vec<String> {
+    filters.sort_by_key(|filter| filter.order);
+    let is_given = |id: &str| ids.contains(&id);
+    let (known, unknown): (Vec<&str>, Vec<&str>) = ids.iter()
+        .partition(|id| filters.iter().any(|filter| filter.id == **id));
+    let mut known = known.into_iter();
+
+    // Each position held by a given filter goes to the next given filter in the new order
+    let mut order: Vec<String> = filters.into_iter()
+        .filter_map(|filter| {
+            if is_given(&filter.id) {
+                known.next().map(str::to_string)
+            } else {
+                Some(filter.id)
+            }
+        })
+        .collect();
+    order.extend(unknown.into_iter().map(str::to_string));
+    order
+}
+
+/// Build the request for a single filter.
+fn filter_request(id: &str) -> ApiRequest {
+    ApiRequest::sync_get("/filters/get")
+        .query("filter_id", id)
+        .resource("filter", id)
+}
@@ -0,0 +1,21 @@
+use serde::Deserialize;
+use crate::color::Color;
+
+/// Represents a saved filter, as returned by the API.
+#[derive(Debug, Clone, Deserialize)]
+#[allow(missing_docs)]
+pub struct Filter {
+    pub id: String,
+    pub name: String,
+
+    /// The filter query, e.g. `today | overdue`. See
+    /// <https://todoist.com/help/articles/introduction-to-filters-V98wIH>.
+    pub query: String,
+
+    pub color: Color,
+
+    #[serde(rename = "item_order")]
+    pub order: i32,
+
+    pub is_favorite: bool,
+}
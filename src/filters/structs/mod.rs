@@ -0,0 +1,3 @@
+pub mod filter;
+pub mod new_filter;
+pub mod update_filter;
@@ -0,0 +1,50 @@
+//! Defines the structure used to create a new filter.
+
+
+use crate::color::Color;
+use crate::err::TodoistAPIError;
+use crate::filters::{get_filter_by_id, Filter};
+use crate::sync::CommandBatch;
+use crate::TodoistUser;
+#[cfg(feature = "async")]
+use crate::AsyncTodoistClient;
+#[cfg(feature = "async")]
+use crate::filters::get_filter_by_id_async;
+
+
+/// The structure used to create a new filter. Create an instance of this struct with the chosen
+/// options, then call the `upload` method to upload it to the Todoist API. The `name` and `query`
+/// fields are mandatory, but the others are optional.
+///
+/// - If `order` is not specified, the filter will be added to the end of the list.
+/// - If `color` is not specified, the filter will be given the default API color.
+/// - If `is_favorite` is not specified, the filter will not be marked as a favorite.
+#[derive(Debug, Clone)]
+#[allow(missing_docs)]
+pub struct NewFilter {
+    pub name: String,
+    pub query: String,
+    pub order: Option<i32>,
+    pub color: Option<Color>,
+    pub is_favorite: Option<bool>,
+}
+
+
+impl NewFilter {
+    /// Create the filter, returning it as Todoist stored it.
+    pub fn upload(&self, user: &TodoistUser) -> Result<Filter, TodoistAPIError> {
+        let mut batch = CommandBatch::new();
+        let temp_id = batch.add_filter(self);
+        let results = batch.submit(user)?.into_result()?;
+        get_filter_by_id(user, results.real_id(&temp_id).unwrap_or(&temp_id))
+    }
+
+    /// Asynchronous version of `upload()`.
+    #[cfg(feature = "async")]
+    pub async fn upload_async(&self, client: &AsyncTodoistClient) -> Result<Filter, TodoistAPIError> {
+        let mut batch = CommandBatch::new();
+        let temp_id = batch.add_filter(self);
+        let results = batch.submit_async(client).await?.into_result()?;
+        get_filter_by_id_async(client, results.real_id(&temp_id).unwrap_or(&temp_id)).await
+    }
+}
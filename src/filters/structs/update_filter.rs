@@ -0,0 +1,47 @@
+//! Defines the structure used to update an existing filter
+
+
+use crate::color::Color;
+use crate::err::TodoistAPIError;
+use crate::filters::{get_filter_by_id, Filter};
+use crate::sync::CommandBatch;
+use crate::TodoistUser;
+#[cfg(feature = "async")]
+use crate::AsyncTodoistClient;
+#[cfg(feature = "async")]
+use crate::filters::get_filter_by_id_async;
+
+/// The structure used to update an existing filter. The name, query, order, color and
+/// is_favorite fields can all be updated. Any field left None will not be updated.
+///
+/// Once selecting the appropriate options, call the `update` method with the ID of the filter to
+/// apply the changes.
+#[derive(Debug, Clone)]
+#[allow(missing_docs)]
+pub struct UpdateFilter {
+    pub name: Option<String>,
+    pub query: Option<String>,
+    pub order: Option<i32>,
+    pub color: Option<Color>,
+    pub is_favorite: Option<bool>,
+}
+
+
+impl UpdateFilter {
+    /// Update the filter with the given ID.
+    pub fn update(&self, user: &TodoistUser, id: &str) -> Result<Filter, TodoistAPIError> {
+        let mut batch = CommandBatch::new();
+        batch.update_filter(id, self);
+        batch.submit(user)?.into_result()?;
+        get_filter_by_id(user, id)
+    }
+
+    /// Asynchronous version of `update()`.
+    #[cfg(feature = "async")]
+    pub async fn update_async(&self, client: &AsyncTodoistClient, id: &str) -> Result<Filter, TodoistAPIError> {
+        let mut batch = CommandBatch::new();
+        batch.update_filter(id, self);
+        batch.submit_async(client).await?.into_result()?;
+        get_filter_by_id_async(client, id).await
+    }
+}
@@ -1,49 +1,264 @@
+use reqwest::header::HeaderMap;
+use reqwest::{Method, StatusCode};
 use serde::de::DeserializeOwned;
-use crate::err::TodoistAPIError;
+use serde::Serialize;
+use crate::err::{RequestInfo, TodoistAPIError};
+use crate::retry::retry_after;
+use crate::transport::HttpResponse;
 
-/// Takes a Result<Response, Error> from a Reqwest request and (hopefully) returns the deserialised JSON payload
+/// The path of the REST v2 API, relative to the client's base URL.
+pub const REST_V2_PATH: &str = "/rest/v2";
+
+/// The path of the Sync v9 API, relative to the client's base URL.
+pub const SYNC_V9_PATH: &str = "/sync/v9";
+
+/// What tokens and other secrets are replaced with wherever they would be shown or saved: when
+/// clients and profiles are formatted with `{:?}`, and in cassettes.
+pub const REDACTED: &str = "[REDACTED]";
+
+
+/// Describes a single API request, independently of the client which will send it.
+///
+/// The path is relative to the client's base URL; the client adds the host and the authentication
+/// header when it sends the request.
+pub struct ApiRequest {
+    pub method: Method,
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    pub body: Option<Body>,
+
+    /// Sent as the `X-Request-Id` header, so that Todoist can discard duplicates of the request.
+    pub request_id: Option<String>,
+
+    /// Which of the requests of a call that sends several this is, e.g. the index of a chunk of
+    /// commands. A client with a pinned request ID sends `<pinned ID>-<part>` as this request's
+    /// ID, so that each request of the call has its own.
+    pub part: Option<String>,
+
+    /// Whether the request only reads, even though it is a POST (as reads through the Sync API
+    /// are), so can be retried without a request ID.
+    pub read_only: bool,
+
+    /// The kind and ID of the object the request is about, if any, for reporting `NotFound`.
+    pub resource: Option<(&'static str, String)>,
+
+    /// Send the request to this base URL instead of the client's, e.g. for Todoist's OAuth
+    /// endpoints, which aren't on the API server.
+    pub base_url: Option<String>,
+
+    /// Whether to send the client's API token with the request.
+    pub authenticated: bool,
+}
+
+impl ApiRequest {
+    /// Create a request to an arbitrary path on the API server.
+    pub fn new(method: Method, path: String) -> ApiRequest {
+        ApiRequest {
+            method,
+            path,
+            query: Vec::new(),
+            body: None,
+            request_id: None,
+            part: None,
+            read_only: false,
+            resource: None,
+            base_url: None,
+            authenticated: true,
+        }
+    }
+
+    /// A GET request to the given path within the REST v2 API.
+    pub fn get(path: &str) -> ApiRequest {
+        ApiRequest::new(Method::GET, String::from(REST_V2_PATH) + path)
+    }
+
+    /// A POST request to the given path within the REST v2 API, with a new request ID.
+    pub fn post(path: &str) -> ApiRequest {
+        ApiRequest::new(Method::POST, String::from(REST_V2_PATH) + path).with_new_request_id()
+    }
+
+    /// A DELETE request to the given path within the REST v2 API, with a new request ID.
+    pub fn delete(path: &str) -> ApiRequest {
+        ApiRequest::new(Method::DELETE, String::from(REST_V2_PATH) + path).with_new_request_id()
+    }
+
+    /// A GET request to the given path within the Sync v9 API.
+    pub fn sync_get(path: &str) -> ApiRequest {
+        ApiRequest::new(Method::GET, String::from(SYNC_V9_PATH) + path)
+    }
+
+    /// A POST request to the given path within the Sync v9 API, with a new request ID.
+    pub fn sync_post(path: &str) -> ApiRequest {
+        ApiRequest::new(Method::POST, String::from(SYNC_V9_PATH) + path).with_new_request_id()
+    }
+
+    /// A POST request to the given path within the Sync v9 API which only reads, so is sent
+    /// without a request ID.
+    pub fn sync_read(path: &str) -> ApiRequest {
+        ApiRequest {
+            read_only: true,
+            ..ApiRequest::new(Method::POST, String::from(SYNC_V9_PATH) + path)
+        }
+    }
+
+    /// Send the request to the given base URL rather than the client's.
+    pub fn base_url(mut self, base_url: &str) -> ApiRequest {
+        self.base_url = Some(base_url.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Don't send the client's API token with the request.
+    pub fn unauthenticated(mut self) -> ApiRequest {
+        self.authenticated = false;
+        self
+    }
+
+    /// Record the kind and ID of the object the request is about, e.g. `("task", id)`.
+    pub fn resource(mut self, kind: &'static str, id: &str) -> ApiRequest {
+        self.resource = Some((kind, id.to_string()));
+        self
+    }
+
+    /// Mark the request as the given part of a call which sends several requests.
+    pub fn part(mut self, part: &str) -> ApiRequest {
+        self.part = Some(part.to_string());
+        self
+    }
+
+    /// Give the request a newly generated request ID.
+    pub fn with_new_request_id(mut self) -> ApiRequest {
+        self.request_id = Some(uuid::Uuid::new_v4().to_string());
+        self
+    }
+
+    /// Add a query string parameter.
+    pub fn query(mut self, name: &str, value: &str) -> ApiRequest {
+        self.query.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// The path of the request with the ID of the object it is about replaced by `{id}`, e.g.
+    /// `/rest/v2/tasks/{id}/close`, which identifies the endpoint regardless of the object.
+    #[cfg(feature = "tracing")]
+    pub fn endpoint(&self) -> String {
+        match &self.resource {
+            Some((_, id)) => self.path.split('/')
+                .map(|segment| if segment == id { "{id}" } else { segment })
+                .collect::<Vec<_>>()
+                .join("/"),
+            None => self.path.clone(),
+        }
+    }
+
+    /// Whether the request can safely be sent more than once. Anything except a POST is, and a
+    /// POST is too if it only reads or has a request ID, as Todoist will ignore the duplicate.
+    pub fn is_repeatable(&self) -> bool {
+        self.method != Method::POST || self.read_only || self.request_id.is_some()
+    }
+
+    /// Serialise the given value as the JSON body of the request.
+    pub fn json<T: Serialize + ?Sized>(mut self, body: &T) -> Result<ApiRequest, TodoistAPIError> {
+        let body = serde_json::to_value(body).map_err(TodoistAPIError::SerdeSerialisationError)?;
+        self.body = Some(Body::Json(body));
+        Ok(self)
+    }
+
+    /// Send the given parts as a multipart body.
+    pub fn multipart(mut self, parts: Vec<Part>) -> ApiRequest {
+        self.body = Some(Body::Multipart(parts));
+        self
+    }
+
+    /// Send the given fields as a form-encoded body.
+    pub fn form(mut self, fields: &[(&str, &str)]) -> ApiRequest {
+        let fields = fields.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect();
+        self.body = Some(Body::Form(fields));
+        self
+    }
+}
+
+
+/// The body of an `ApiRequest`.
+pub enum Body {
+    /// A JSON body.
+    Json(serde_json::Value),
+
+    /// A form-encoded (`application/x-www-form-urlencoded`) body.
+    Form(Vec<(String, String)>),
+
+    /// A multipart (`multipart/form-data`) body, e.g. for uploading a file.
+    Multipart(Vec<Part>),
+}
+
+
+/// A single field of a multipart body.
+pub struct Part {
+    pub name: String,
+
+    /// The name of the file the field holds, if it is a file.
+    pub file_name: Option<String>,
+
+    pub data: Vec<u8>,
+}
+
+
+/// Build the error for an unsuccessful HTTP status, given the request that was sent, the URL it
+/// was sent to, and the status, headers and text of the response.
+pub fn error_for_status(
+    request: &ApiRequest,
+    url: String,
+    status: StatusCode,
+    headers: &HeaderMap,
+    message: String,
+) -> TodoistAPIError {
+    let request_info = RequestInfo { method: request.method.clone(), url };
+    match status {
+        StatusCode::BAD_REQUEST => TodoistAPIError::BadRequest { message, request: request_info },
+        StatusCode::UNAUTHORIZED => TodoistAPIError::Unauthorized { message, request: request_info },
+        StatusCode::FORBIDDEN => TodoistAPIError::Forbidden { message, request: request_info },
+        StatusCode::NOT_FOUND => {
+            let (resource, id) = match &request.resource {
+                Some((kind, id)) => (kind.to_string(), Some(id.clone())),
+                None => (request.path.clone(), None),
+            };
+            TodoistAPIError::NotFound { resource, id, request: request_info }
+        }
+        StatusCode::TOO_MANY_REQUESTS => TodoistAPIError::RateLimited {
+            retry_after: retry_after(headers),
+            request: request_info,
+        },
+        status if status.is_server_error() => TodoistAPIError::ServerError { status, message, request: request_info },
+        status => TodoistAPIError::UnexpectedStatus { status, message, request: request_info },
+    }
+}
+
+
+/// Takes a Result<Response, Error> from sending a request and (hopefully) returns the deserialised JSON payload
 ///
-/// This does three steps:
-/// 1. Make sure that the Result was Ok; if it was Err then return a Todoist error with that payload
-/// 2. Make sure that the HTTP response was successful; if it wasn't then return a Todoist error with the bad status code
-/// 3. Try to deserialise the JSON payload; if it fails then return a Todoist error with the deserialisation error
+/// This does two steps:
+/// 1. Make sure that the Result was Ok; if it was Err (including because of an unsuccessful HTTP
+///    status) then return that error
+/// 2. Try to deserialise the JSON payload; if it fails then return a Todoist error with the deserialisation error
 ///
 /// If that was all successful, return the payload
-pub fn get_from_reqwest_response<T: DeserializeOwned>(response: Result<reqwest::blocking::Response, reqwest::Error>)
+pub fn get_from_response<T: DeserializeOwned>(response: Result<HttpResponse, TodoistAPIError>)
                                 -> Result<T, TodoistAPIError> {
     // Unpack the meaning of the response, making sure there was no error in making the HTTP request
-    let response = match response {
-        Ok(rsp) => rsp,
-        Err(err) => return Err(TodoistAPIError::ReqwestRequestError(err)),
-    };
-
-    // We got an HTTP response; was it successful?
-    if !response.status().is_success() {
-        return Err(TodoistAPIError::UnsuccessfulHTTPStatus(response.status(), response.text().unwrap_or("".to_string())));
-    }
+    let response = response?;
 
     // We got a successful HTTP response. That means it *should* be proper JSON we can deserialise
-    let parsed = response.json::<T>();
+    let parsed = serde_json::from_slice::<T>(&response.body);
     match parsed {
         Ok(data) => Ok(data),
-        Err(err) => Err(TodoistAPIError::ReqwestDeserialisationError(err))
+        Err(err) => Err(TodoistAPIError::DeserialisationError(err))
     }
 }
 
 
 /// Handle a request Result where we expected a 204 No Content response
-/// This can return an error, just like get_from_reqwest_response, but if successful will return ()
-pub fn get_204_from_reqwest_response(response: Result<reqwest::blocking::Response, reqwest::Error>) -> Result<(), TodoistAPIError> {
+/// This can return an error, just like get_from_response, but if successful will return ()
+pub fn get_204_from_response(response: Result<HttpResponse, TodoistAPIError>) -> Result<(), TodoistAPIError> {
     // Unpack the meaning of the response, making sure there was no error in making the HTTP request
-    let response = match response {
-        Ok(rsp) => rsp,
-        Err(err) => return Err(TodoistAPIError::ReqwestRequestError(err)),
-    };
-
-    // We got an HTTP response; was it successful?
-    if !response.status().is_success() {
-        return Err(TodoistAPIError::UnsuccessfulHTTPStatus(response.status(), response.text().unwrap_or("".to_string())));
-    }
-
+    response?;
     Ok(())
 }
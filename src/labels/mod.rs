@@ -25,79 +25,107 @@ pub use structs::new_label::NewLabel;
 pub use structs::update_label::UpdateLabel;
 
 use crate::err::TodoistAPIError;
-use crate::general::{get_204_from_reqwest_response, get_from_reqwest_response};
+use crate::general::{get_204_from_response, get_from_response, ApiRequest};
 use crate::TodoistUser;
+#[cfg(feature = "async")]
+use crate::AsyncTodoistClient;
+use serde_json::json;
 
 
 /// Get a Vec of all the user's personal labels.
 pub fn get_all_personal_labels(user: &TodoistUser) -> Result<Vec<Label>, TodoistAPIError> {
     // Send the API request
-    let client = reqwest::blocking::Client::new();
-    let response = client.get("https://api.todoist.com/rest/v2/labels")
-        .header("Authorization", String::from("Bearer ") + &user.token)
-        .send();
-    get_from_reqwest_response(response)
+    let response = user.send(ApiRequest::get("/labels"));
+    get_from_response(response)
+}
+
+/// Asynchronous version of `get_all_personal_labels()`.
+#[cfg(feature = "async")]
+pub async fn get_all_personal_labels_async(client: &AsyncTodoistClient) -> Result<Vec<Label>, TodoistAPIError> {
+    let response = client.send(ApiRequest::get("/labels")).await;
+    get_from_response(response)
 }
 
 /// Get an individual Label by its ID.
 pub fn get_label_by_id(user: &TodoistUser, id: &str) -> Result<Label, TodoistAPIError> {
     // Send the API request
-    let client = reqwest::blocking::Client::new();
-    let response = client.get("https://api.todoist.com/rest/v2/labels/".to_string() + id)
-        .header("Authorization", String::from("Bearer ") + &user.token)
-        .send();
-    get_from_reqwest_response(response)
+    let response = user.send(ApiRequest::get(&format!("/labels/{}", id)).resource("label", id));
+    get_from_response(response)
+}
+
+/// Asynchronous version of `get_label_by_id()`.
+#[cfg(feature = "async")]
+pub async fn get_label_by_id_async(client: &AsyncTodoistClient, id: &str) -> Result<Label, TodoistAPIError> {
+    let request = ApiRequest::get(&format!("/labels/{}", id))
+        .resource("label", id);
+    let response = client.send(request).await;
+    get_from_response(response)
 }
 
 
 /// Delete the label with the given ID.
 pub fn delete_label_by_id(user: &TodoistUser, id: &str) -> Result<(), TodoistAPIError> {
     // Send the API request
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .delete(format!("https://api.todoist.com/rest/v2/labels/{}", id))
-        .header("Authorization", String::from("Bearer ") + &user.token)
-        .send();
-    get_204_from_reqwest_response(response)
+    let response = user.send(ApiRequest::delete(&format!("/labels/{}", id)).resource("label", id));
+    get_204_from_response(response)
+}
+
+/// Asynchronous version of `delete_label_by_id()`.
+#[cfg(feature = "async")]
+pub async fn delete_label_by_id_async(client: &AsyncTodoistClient, id: &str) -> Result<(), TodoistAPIError> {
+    let request = ApiRequest::delete(&format!("/labels/{}", id))
+        .resource("label", id);
+    let response = client.send(request).await;
+    get_204_from_response(response)
 }
 
 
 /// Get a Vec of all the user's shared labels, just as strings.
 pub fn get_all_shared_labels(user: &TodoistUser) -> Result<Vec<String>, TodoistAPIError> {
     // Send the API request
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .get("https://api.todoist.com/rest/v2/labels/shared")
-        .header("Authorization", String::from("Bearer ") + &user.token)
-        .query(&[("omit_personal", "false")])
-        .send();
-    get_from_reqwest_response(response)
+    let response = user.send(ApiRequest::get("/labels/shared").query("omit_personal", "false"));
+    get_from_response(response)
+}
+
+/// Asynchronous version of `get_all_shared_labels()`.
+#[cfg(feature = "async")]
+pub async fn get_all_shared_labels_async(client: &AsyncTodoistClient) -> Result<Vec<String>, TodoistAPIError> {
+    let request = ApiRequest::get("/labels/shared")
+        .query("omit_personal", "false");
+    let response = client.send(request).await;
+    get_from_response(response)
 }
 
 
 /// Rename a shared label.
 pub fn rename_shared_label(user: &TodoistUser, old_name: &str, new_name: &str) -> Result<(), TodoistAPIError> {
     // Send the API request
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .post("https://api.todoist.com/rest/v2/labels/shared/rename")
-        .header("Authorization", String::from("Bearer ") + &user.token)
-        .header("Content-Type", "application/json")
-        .body(format!("{{\"old_name\": \"{}\", \"new_name\": \"{}\"}}", old_name, new_name))
-        .send();
-    get_204_from_reqwest_response(response)
+    let body = json!({"old_name": old_name, "new_name": new_name});
+    let response = user.send(ApiRequest::post("/labels/shared/rename").json(&body)?);
+    get_204_from_response(response)
+}
+
+/// Asynchronous version of `rename_shared_label()`.
+#[cfg(feature = "async")]
+pub async fn rename_shared_label_async(client: &AsyncTodoistClient, old_name: &str, new_name: &str) -> Result<(), TodoistAPIError> {
+    let body = json!({"old_name": old_name, "new_name": new_name});
+    let response = client.send(ApiRequest::post("/labels/shared/rename").json(&body)?).await;
+    get_204_from_response(response)
 }
 
 
 /// Remove a shared label from all tasks to which it is applied
 pub fn remove_shared_label(user: &TodoistUser, name: &str) -> Result<(), TodoistAPIError> {
     // Send the API request
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .post("https://api.todoist.com/rest/v2/labels/shared/remove")
-        .header("Authorization", String::from("Bearer ") + &user.token)
-        .header("Content-Type", "application/json")
-        .body(format!("{{\"name\": \"{}\"}}", name))
-        .send();
-    get_204_from_reqwest_response(response)
+    let body = json!({"name": name});
+    let response = user.send(ApiRequest::post("/labels/shared/remove").json(&body)?);
+    get_204_from_response(response)
+}
+
+/// Asynchronous version of `remove_shared_label()`.
+#[cfg(feature = "async")]
+pub async fn remove_shared_label_async(client: &AsyncTodoistClient, name: &str) -> Result<(), TodoistAPIError> {
+    let body = json!({"name": name});
+    let response = client.send(ApiRequest::post("/labels/shared/remove").json(&body)?).await;
+    get_204_from_response(response)
 }
@@ -4,9 +4,11 @@
 use serde::Serialize;
 use crate::color::Color;
 use crate::err::TodoistAPIError;
-use crate::general::get_from_reqwest_response;
+use crate::general::{get_from_response, ApiRequest};
 use crate::labels::Label;
 use crate::TodoistUser;
+#[cfg(feature = "async")]
+use crate::AsyncTodoistClient;
 
 
 /// The structure used to create a new label. Create an instance of this struct with the chosen
@@ -36,12 +38,14 @@ impl NewLabel {
     /// Upload the label to the Todoist API. Returns a full `Label` struct.
     pub fn upload(&self, user: &TodoistUser) -> Result<Label, TodoistAPIError> {
         // Send the API request
-        let client = reqwest::blocking::Client::new();
-        let response = client
-            .post("https://api.todoist.com/rest/v2/labels")
-            .header("Authorization", String::from("Bearer ") + &user.token)
-            .json(&self)
-            .send();
-        get_from_reqwest_response(response)
+        let response = user.send(ApiRequest::post("/labels").json(self)?);
+        get_from_response(response)
+    }
+
+    /// Asynchronous version of `upload()`.
+    #[cfg(feature = "async")]
+    pub async fn upload_async(&self, client: &AsyncTodoistClient) -> Result<Label, TodoistAPIError> {
+        let response = client.send(ApiRequest::post("/labels").json(self)?).await;
+        get_from_response(response)
     }
 }
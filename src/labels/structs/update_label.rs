@@ -3,9 +3,11 @@
 use serde::Serialize;
 use crate::color::Color;
 use crate::err::TodoistAPIError;
-use crate::general::get_from_reqwest_response;
+use crate::general::{get_from_response, ApiRequest};
 use crate::labels::Label;
 use crate::TodoistUser;
+#[cfg(feature = "async")]
+use crate::AsyncTodoistClient;
 
 /// The structure used to update an existing label. The name, order, color and is_favorite fields
 /// can all be updated. Any field left None will not be updated.
@@ -33,12 +35,20 @@ impl UpdateLabel {
     /// Update the label with the given ID.
     pub fn update(&self, user: &TodoistUser, id: &str) -> Result<Label, TodoistAPIError> {
         // Send the API request
-        let client = reqwest::blocking::Client::new();
-        let response = client
-            .post(format!("https://api.todoist.com/rest/v2/labels/{}", id))
-            .header("Authorization", String::from("Bearer ") + &user.token)
-            .json(&self)
-            .send();
-        get_from_reqwest_response(response)
+        let request = ApiRequest::post(&format!("/labels/{}", id))
+            .resource("label", id)
+            .json(self)?;
+        let response = user.send(request);
+        get_from_response(response)
+    }
+
+    /// Asynchronous version of `update()`.
+    #[cfg(feature = "async")]
+    pub async fn update_async(&self, client: &AsyncTodoistClient, id: &str) -> Result<Label, TodoistAPIError> {
+        let request = ApiRequest::post(&format!("/labels/{}", id))
+            .resource("label", id)
+            .json(self)?;
+        let response = client.send(request).await;
+        get_from_response(response)
     }
 }
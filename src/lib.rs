@@ -6,14 +6,20 @@
 //!
 //! Before using the Todoist API, you must have an API token. To find the API token for your own
 //! user account, go to <https://app.todoist.com/app/settings/integrations/developer> and
-//! view the text box under "API token". This should be a 40-byte hexadecimal number.
+//! view the text box under "API token". This should be a 40-byte hexadecimal number. If you are
+//! writing an app which other people connect their own Todoist accounts to, see the `oauth` module
+//! for getting a token for each of them instead. To read the token from an environment variable
+//! or a config file with several accounts, see the `config` module.
 //!
 //! Once you have your API token, you can create a `TodoistUser` struct, then pass a reference
 //! to this to any function in the crate, which will perform the relevant underlying API call.
+//! Create it once and reuse it, since it holds a pool of connections to Todoist's servers. If you
+//! need to change how requests are made (e.g. the base URL, timeouts or a proxy), see the `client`
+//! module.
 //!
 //! # Examples
 //! Here's a basic example which gets and prints all of the user's projects:
-//! ```
+//! ```no_run
 //! use todoist_v2_rest::{TodoistUser, projects};
 //! let user = TodoistUser::new("a2a72c2f394b265bb798d5dc4ef55be51443d519");
 //! let user_projects = projects::get_projects(&user).expect("Couldn't load project list");
@@ -33,7 +39,7 @@
 //! (so we can either explicitly specify `false` to the API, or we can just not mention it). To do
 //! this, we create a new `NewProject` struct, fill in the fields we want, then call its
 //! `upload()` method. The full code to do this would be:
-//! ```
+//! ```no_run
 //! use todoist_v2_rest::{TodoistUser, projects, color::Color, projects::ViewStyle};
 //! let user = TodoistUser::new("a2a72c2f394b265bb798d5dc4ef55be51443d519");
 //! let new_project = projects::NewProject {
@@ -51,7 +57,27 @@
 //! by the API.
 //!
 //! For each of the five object types, full descriptions of the functions and types are given in
-//! the top-level corresponding module.
+//! the top-level corresponding module. To get all of them in one request, and then only what has
+//! changed since, see the `sync` module. Saved filters and reminders, which only the Sync API
+//! covers, are in the `filters` and `reminders` modules, the activity log is in the `activity`
+//! module, and the user's own profile and productivity stats are in the `user` module.
+//!
+//! # Async
+//!
+//! By default every function blocks until its API call has finished. If you enable the `async`
+//! feature, every function and `upload()`/`update()` method also gets an `_async` version (e.g.
+//! `projects::get_projects_async()` or `NewTask::upload_async()`), which takes an
+//! `AsyncTodoistClient` instead of a `TodoistUser` and returns a future. The types used for
+//! projects, tasks etc. are the same either way.
+//! ```no_run
+//! # #[cfg(feature = "async")]
+//! # async fn example() {
+//! use todoist_v2_rest::{AsyncTodoistClient, projects};
+//! let client = AsyncTodoistClient::new("a2a72c2f394b265bb798d5dc4ef55be51443d519");
+//! let user_projects = projects::get_projects_async(&client).await.expect("Couldn't load project list");
+//! println!("{:?}", user_projects);
+//! # }
+//! ```
 //!
 //! Since basically all of the functions in this crate make an API call which could fail for any
 //! number of reasons, they all return a Result with a TodoistAPIError error type. See the `err`
@@ -80,29 +106,1529 @@ pub mod sections;
 
 pub mod comments;
 
+pub mod sync;
+
+pub mod filters;
+
+pub mod reminders;
+
+pub mod activity;
+
+pub mod user;
+
+pub mod client;
+
+pub mod retry;
+
+pub mod rate_limit;
+
+pub mod transport;
+
+pub mod cassette;
+
+pub mod oauth;
+
+pub mod config;
+
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+
 mod general;
 
+pub use client::{TodoistClient, TodoistClientBuilder};
+#[cfg(feature = "async")]
+pub use client::AsyncTodoistClient;
+
 
 /// Represents a Todoist user, holding the user's API token.
 ///
-/// Create an instance with `TodoistUser::new(<api_token>)`. A reference to a `TodoistUser` must be
-/// passed to any function which makes an API request, in order to authenticate the request with
-/// Todoist's servers.
-pub struct TodoistUser {
-    token: String,
-}
+/// This is the same type as `TodoistClient`; create an instance with `TodoistUser::new(<api_token>)`,
+/// or use `TodoistClient::builder(<api_token>)` to change settings such as the base URL. A
+/// reference to a `TodoistUser` must be passed to any function which makes an API request, in
+/// order to authenticate the request with Todoist's servers.
+pub type TodoistUser = TodoistClient;
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::time::Duration;
+    use serde_json::{json, Value};
+    use crate::err::TodoistAPIError;
+    use crate::retry::RetryPolicy;
+    use crate::cassette::{RecordingTransport, ReplayTransport};
+    use crate::config::Config;
+    use crate::oauth::{OAuthApp, Scope};
+    use crate::rate_limit::RateLimiter;
+    use crate::testing::{FakeTodoist, FAKE_AUTHORIZATION_CODE};
+    use crate::transport::{HttpRequest, HttpResponse, MockTransport, ReqwestTransport};
+    use crate::{activity, comments, filters, labels, projects, reminders, sections, sync, tasks, user, TodoistClient};
+    use crate::color::Color;
+
+    /// A client which sends requests to the given mock, retrying quickly.
+    fn mock_client(transport: &MockTransport) -> TodoistClient {
+        TodoistClient::builder("0123456789abcdef")
+            .transport(transport.clone())
+            .retry_policy(RetryPolicy {
+                initial_backoff: Duration::from_millis(1),
+                jitter: 0.0,
+                ..RetryPolicy::default()
+            })
+            .build()
+            .unwrap()
+    }
+
+    /// A task as the REST API would return it.
+    fn task_json(id: &str, content: &str) -> Value {
+        json!({
+            "id": id,
+            "project_id": "2203306141",
+            "section_id": null,
+            "content": content,
+            "description": "",
+            "is_completed": false,
+            "labels": [],
+            "parent_id": null,
+            "order": 1,
+            "priority": 1,
+            "due": null,
+            "url": format!("https://todoist.com/showTask?id={}", id),
+            "comment_count": 0,
+            "created_at": "2019-12-11T22:36:50.000000Z",
+            "creator_id": "2671355",
+            "assignee_id": null,
+            "assigner_id": null,
+            "duration": null
+        })
+    }
+
+    #[test]
+    fn sends_authenticated_request_to_base_url() {
+        let transport = MockTransport::new();
+        transport.push_response(HttpResponse::json(200, &json!([task_json("1", "Buy milk")])));
+        let client = TodoistClient::builder("0123456789abcdef")
+            .base_url("http://localhost:8080/")
+            .user_agent("tests")
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        let active = tasks::get_active_tasks_by_project(&client, "2203306141").unwrap();
+
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].content, "Buy milk");
+        let request = &transport.requests()[0];
+        assert_eq!(request.method, reqwest::Method::GET);
+        assert_eq!(request.url, "http://localhost:8080/rest/v2/tasks?project_id=2203306141");
+        assert_eq!(request.header("authorization"), Some("Bearer 0123456789abcdef"));
+        assert_eq!(request.header("User-Agent"), Some("tests"));
+        assert_eq!(request.header("X-Request-Id"), None);
+    }
+
+    #[test]
+    fn mutating_request_has_json_body_and_request_id() {
+        let transport = MockTransport::new();
+        transport.push_response(HttpResponse::json(200, &task_json("2", "Write tests")));
+        let client = mock_client(&transport);
+
+        let mut new_task = tasks::NewTask::new("Write tests");
+        new_task.priority = Some(4);
+        new_task.upload(&client).unwrap();
+
+        let request = &transport.requests()[0];
+        assert_eq!(request.method, reqwest::Method::POST);
+        assert_eq!(request.json::<Value>(), Some(json!({"content": "Write tests", "priority": 4})));
+        assert_eq!(request.header("Content-Type"), Some("application/json"));
+        assert!(request.header("X-Request-Id").is_some());
+    }
 
-impl TodoistUser {
-    /// Create a new `TodoistUser`, passing in your API token.
-    pub fn new(api_token: &str) -> TodoistUser {
-        TodoistUser {
-            token: api_token.to_string()
+    #[test]
+    fn retries_keep_the_same_request_id() {
+        let transport = MockTransport::new();
+        transport.push_failure("connection reset");
+        transport.push_response(HttpResponse::new(503, b"Service Unavailable"));
+        transport.push_response(HttpResponse::new(204, b""));
+        let client = mock_client(&transport);
+
+        tasks::close_task_by_id(&client, "2995104339").unwrap();
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 3);
+        let request_id = requests[0].header("X-Request-Id").unwrap();
+        assert!(requests.iter().all(|request| request.header("X-Request-Id") == Some(request_id)));
+    }
+
+    #[test]
+    fn pinned_request_id_is_sent() {
+        let transport = MockTransport::new();
+        transport.push_response(HttpResponse::new(204, b""));
+        let client = mock_client(&transport);
+
+        projects::delete_project_by_id(&client.with_request_id("my-id"), "2203306141").unwrap();
+
+        assert_eq!(transport.requests()[0].header("X-Request-Id"), Some("my-id"));
+    }
+
+    #[test]
+    fn rate_limited_request_is_retried() {
+        let transport = MockTransport::new();
+        transport.push_response(HttpResponse::new(429, b"Too Many Requests").with_header("Retry-After", "0"));
+        transport.push_response(HttpResponse::json(200, &json!([])));
+        let client = mock_client(&transport);
+
+        assert!(projects::get_projects(&client).unwrap().is_empty());
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[test]
+    fn rate_limiter_drains_and_refills() {
+        let limiter = RateLimiter::new(3, Duration::from_millis(300));
+        assert_eq!(limiter.remaining(), 3);
+
+        assert!(limiter.try_acquire());
+        assert!(limiter.try_acquire());
+        assert_eq!(limiter.remaining(), 1);
+        assert!(limiter.clone().try_acquire());
+        assert!(!limiter.try_acquire());
+        assert_eq!(limiter.remaining(), 0);
+
+        // A whole window refills the bucket, but never beyond the quota
+        std::thread::sleep(Duration::from_millis(400));
+        assert_eq!(limiter.remaining(), 3);
+        assert!(limiter.try_acquire());
+    }
+
+    #[test]
+    fn rate_limiter_must_allow_requests() {
+        for (quota, window) in [(0, Duration::from_secs(1)), (10, Duration::ZERO)] {
+            let err = RateLimiter::try_new(quota, window).unwrap_err();
+            assert!(matches!(err, TodoistAPIError::InvalidRateLimiter(_)));
+            assert!(std::panic::catch_unwind(|| RateLimiter::new(quota, window)).is_err());
         }
+        assert_eq!(RateLimiter::try_new(1, Duration::from_secs(1)).unwrap().quota(), 1);
     }
-}
 
-#[cfg(test)]
-mod tests {
+    #[test]
+    fn rate_limited_client_waits_for_budget() {
+        let transport = MockTransport::new();
+        for _ in 0..3 {
+            transport.push_response(HttpResponse::json(200, &json!([])));
+        }
+        let client = TodoistClient::builder("0123456789abcdef")
+            .transport(transport.clone())
+            .rate_limiter(RateLimiter::new(1, Duration::from_millis(100)))
+            .build()
+            .unwrap();
+
+        let start = std::time::Instant::now();
+        for _ in 0..3 {
+            assert!(projects::get_projects(&client).unwrap().is_empty());
+        }
+        assert!(start.elapsed() >= Duration::from_millis(190));
+        assert_eq!(transport.requests().len(), 3);
+    }
+
+    #[test]
+    fn retry_after_accepts_seconds_or_a_date() {
+        let retry_after = |value: &str| {
+            let mut headers = reqwest::header::HeaderMap::new();
+            headers.insert(reqwest::header::RETRY_AFTER, value.parse().unwrap());
+            crate::retry::retry_after(&headers)
+        };
+        assert_eq!(retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), Some(Duration::ZERO));
+
+        // 2100-01-01 is 4102444800 seconds after the Unix epoch
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap();
+        let wait = retry_after("Fri, 01 Jan 2100 00:00:00 GMT").unwrap();
+        assert!((wait + now).as_secs().abs_diff(4_102_444_800) <= 1);
+        let wait = retry_after("Thu, 29 Feb 2096 12:30:15 GMT").unwrap();
+        assert!((wait + now).as_secs().abs_diff(3_981_357_015) <= 1);
+
+        assert_eq!(retry_after("soon"), None);
+        assert_eq!(retry_after("Wed, 21 Foo 2015 07:28:00 GMT"), None);
+        assert_eq!(retry_after("Wednesday, 21-Oct-15 07:28:00 GMT"), None);
+
+        // Dates which don't exist, or are too far off to represent, are ignored rather than panicking
+        assert_eq!(retry_after("Sat, 31 Feb 2099 00:00:00 GMT"), None);
+        assert_eq!(retry_after("Sun, 29 Feb 2099 00:00:00 GMT"), None);
+        assert_eq!(retry_after("Thu, 31 Apr 2099 00:00:00 GMT"), None);
+        assert_eq!(retry_after("Wed, 21 Oct 99999999999999 07:28:00 GMT"), None);
+        assert_eq!(retry_after("Wed, 21 Oct 999999999999999999999 07:28:00 GMT"), None);
+        assert_eq!(retry_after("Fri, 31 Dec 10000 23:59:59 GMT"), None);
+        assert!(retry_after("Fri, 31 Dec 9999 23:59:59 GMT").is_some());
+    }
+
+    #[test]
+    fn gives_up_after_max_attempts() {
+        let transport = MockTransport::new();
+        for _ in 0..3 {
+            transport.push_response(HttpResponse::new(500, b"Internal Server Error"));
+        }
+        let client = mock_client(&transport);
+
+        let err = projects::get_projects(&client).unwrap_err();
+
+        assert!(matches!(err, TodoistAPIError::ServerError { .. }));
+        assert!(err.is_retryable());
+        assert_eq!(transport.requests().len(), 3);
+    }
+
+    #[test]
+    fn not_found_names_the_object() {
+        let transport = MockTransport::new();
+        transport.push_response(HttpResponse::new(404, b"Task not found"));
+        let client = mock_client(&transport);
+
+        let err = tasks::get_individual_task_by_id(&client, "123").unwrap_err();
+
+        match err {
+            TodoistAPIError::NotFound { resource, id, request } => {
+                assert_eq!(resource, "task");
+                assert_eq!(id.as_deref(), Some("123"));
+                assert_eq!(request.url, "https://api.todoist.com/rest/v2/tasks/123");
+            }
+            other => panic!("Expected NotFound, got {:?}", other),
+        }
+        assert_eq!(transport.requests().len(), 1);
+    }
+
+    #[test]
+    fn unauthorized_is_not_retried() {
+        let transport = MockTransport::new();
+        transport.push_response(HttpResponse::new(401, b"Forbidden"));
+        let client = mock_client(&transport);
+
+        let err = projects::get_projects(&client).unwrap_err();
+
+        assert!(matches!(err, TodoistAPIError::Unauthorized { .. }));
+        assert!(!err.is_retryable());
+        assert_eq!(transport.remaining_responses(), 0);
+    }
+
+    /// The fields of a request's form-encoded body.
+    fn form_fields(request: &HttpRequest) -> HashMap<String, String> {
+        let body = String::from_utf8(request.body.clone().unwrap_or_default()).unwrap();
+        reqwest::Url::parse(&format!("http://localhost/?{}", body)).unwrap()
+            .query_pairs().into_owned().collect()
+    }
+
+    #[test]
+    fn full_then_incremental_sync() {
+        let transport = MockTransport::new();
+        transport.push_response(HttpResponse::json(200, &json!({
+            "sync_token": "token-1",
+            "full_sync": true,
+            "items": [{
+                "id": "6X7rM8997g3RQmvh", "project_id": "6Jf8VQXxpwv56VQ7", "section_id": null,
+                "content": "Buy milk", "description": "", "checked": false, "labels": ["food"],
+                "parent_id": null, "child_order": 2, "priority": 4, "is_deleted": false,
+                "due": {"date": "2016-12-01T12:00:00Z", "timezone": "Europe/Paris",
+                        "string": "every day at 12", "lang": "en", "is_recurring": true},
+                "added_at": "2016-12-01T10:00:00.000000Z", "added_by_uid": "2671355",
+                "responsible_uid": null, "assigned_by_uid": null, "duration": null
+            }],
+            "projects": [{
+                "id": "6Jf8VQXxpwv56VQ7", "name": "Inbox", "color": "grey", "parent_id": null,
+                "child_order": 0, "shared": false, "is_favorite": false, "inbox_project": true,
+                "view_style": "list", "is_deleted": false
+            }],
+            "sections": [{"id": "6Jf8VQXxpwv56VQ8", "project_id": "6Jf8VQXxpwv56VQ7",
+                          "section_order": 1, "name": "Dairy", "is_deleted": false}],
+            "labels": [{"id": "2156154810", "name": "food", "color": "berry_red",
+                        "item_order": 0, "is_favorite": true, "is_deleted": false}],
+            "notes": [{"id": "6X7rfFVPjhvv84XG", "item_id": "6X7rM8997g3RQmvh",
+                       "posted_at": "2016-12-01T11:00:00.000000Z", "content": "Semi-skimmed",
+                       "file_attachment": null, "is_deleted": false}],
+            "project_notes": []
+        })));
+        transport.push_response(HttpResponse::json(200, &json!({
+            "sync_token": "token-2",
+            "full_sync": false,
+            "items": [{"id": "6X7rM8997g3RQmvh", "is_deleted": true}],
+            "notes": [{"id": "6X7rfFVPjhvv84XG", "is_deleted": true}],
+            "project_notes": [{"id": "6X7rfFVPjhvv84XH", "project_id": "6Jf8VQXxpwv56VQ7",
+                               "posted_at": "2016-12-02T11:00:00.000000Z", "content": "Tidy up",
+                               "file_attachment": null, "is_deleted": false}]
+        })));
+        let client = mock_client(&transport);
+
+        let everything = sync::full_sync(&client).unwrap();
+        assert!(everything.full_sync);
+        let task = &everything.tasks[0];
+        assert_eq!((task.content.as_str(), task.order, task.priority), ("Buy milk", 2, 4));
+        let due = task.due.as_ref().unwrap();
+        assert_eq!(due.date, "2016-12-01");
+        assert_eq!(due.datetime.as_deref(), Some("2016-12-01T12:00:00Z"));
+        assert!(everything.projects[0].is_inbox_project);
+        assert_eq!(everything.sections[0].name, "Dairy");
+        assert!(everything.labels[0].is_favorite);
+        assert_eq!(everything.comments.len(), 1);
+
+        let changes = sync::incremental_sync(&client, &everything.sync_token).unwrap();
+        assert!(!changes.full_sync);
+        assert_eq!(changes.sync_token, "token-2");
+        assert!(changes.tasks.is_empty());
+        assert_eq!(changes.deleted.tasks, vec!["6X7rM8997g3RQmvh"]);
+        assert_eq!(changes.deleted.comments, vec!["6X7rfFVPjhvv84XG"]);
+        assert_eq!(changes.comments.len(), 1);
+
+        let requests = transport.requests();
+        assert_eq!(requests[0].url, "https://api.todoist.com/sync/v9/sync");
+        assert_eq!(form_fields(&requests[0])["sync_token"], "*");
+        assert_eq!(form_fields(&requests[1])["sync_token"], "token-1");
+    }
+
+    #[test]
+    fn command_batches_are_chunked_with_temp_ids() {
+        let mut batch = sync::CommandBatch::new();
+        let project_id = batch.add_project(&projects::NewProject {
+            name: "Template".to_string(),
+            parent_id: None,
+            color: Some(Color::Blue),
+            is_favorite: None,
+            view_style: None,
+        });
+        for i in 0..sync::COMMAND_LIMIT {
+            let mut task = tasks::NewTask::new(&format!("Step {}", i));
+            task.project_id = Some(project_id.clone());
+            batch.add_task(&task);
+        }
+        batch.close_task("2995104339");
+        assert_eq!(batch.len(), 102);
+
+        // Command UUIDs are fixed when they are added, so find them by sending the first request
+        // to a mock which fails it
+        let probe = MockTransport::new();
+        probe.push_failure("probe");
+        let probe_client = TodoistClient::builder("0123456789abcdef")
+            .transport(probe.clone())
+            .retry_policy(RetryPolicy::none())
+            .build()
+            .unwrap();
+        assert!(batch.submit(&probe_client).is_err());
+        let first: Vec<Value> = serde_json::from_str(&form_fields(&probe.requests()[0])["commands"]).unwrap();
+        assert_eq!(first.len(), sync::COMMAND_LIMIT);
+
+        // Fail one task, and leave out the statuses of the second request
+        let mut status = serde_json::Map::new();
+        for command in &first {
+            status.insert(command["uuid"].as_str().unwrap().to_string(), json!("ok"));
+        }
+        status.insert(first[50]["uuid"].as_str().unwrap().to_string(),
+                      json!({"error_code": 20, "error": "Invalid argument value", "http_code": 400}));
+        let transport = MockTransport::new();
+        transport.push_response(HttpResponse::json(200, &json!({
+            "sync_status": status,
+            "temp_id_mapping": {project_id.as_str(): "6Jf8VQXxpwv56VQ7"}
+        })));
+        transport.push_response(HttpResponse::json(200, &json!({"sync_status": {}, "temp_id_mapping": {}})));
+        let results = batch.submit(&mock_client(&transport)).unwrap();
+
+        assert_eq!(results.results.len(), 102);
+        assert_eq!(results.real_id(&project_id), Some("6Jf8VQXxpwv56VQ7"));
+        assert!(!results.is_ok());
+        assert_eq!(results.results[50].error.as_ref().unwrap().error_code, 20);
+        assert!(results.results[..50].iter().all(|result| result.error.is_none()));
+        assert!(results.results[101].error.is_some());
+
+        // The second request refers to the project by its real ID
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 2);
+        let second: Vec<Value> = serde_json::from_str(&form_fields(&requests[1])["commands"]).unwrap();
+        assert_eq!(second[0]["args"]["project_id"], "6Jf8VQXxpwv56VQ7");
+        assert_eq!(second[1]["type"], "item_close");
+        assert_eq!(first[0]["args"]["color"], "blue");
+    }
+
+    #[test]
+    fn completed_tasks_are_paginated() {
+        let record = |id: &str| json!({
+            "id": id, "task_id": format!("task-{}", id), "content": "Buy milk",
+            "project_id": "2203306141", "section_id": null, "note_count": 1,
+            "completed_at": "2024-01-31T09:00:00.000000Z", "user_id": "2671355", "meta_data": null,
+            "item_object": {"id": format!("task-{}", id), "project_id": "2203306141",
+                            "content": "Buy milk", "checked": true, "child_order": 1}
+        });
+        let transport = MockTransport::new();
+        transport.push_response(HttpResponse::json(200, &json!({"items": [record("1"), record("2")]})));
+        transport.push_response(HttpResponse::json(200, &json!({"items": [record("3")]})));
+        let client = mock_client(&transport);
+
+        let query = tasks::CompletedTasksQuery {
+            project_id: Some("2203306141".to_string()),
+            since: Some("2024-01-01T00:00".to_string()),
+            limit: Some(2),
+            ..Default::default()
+        };
+        let completed = tasks::get_all_completed_tasks(&client, &query).unwrap();
+
+        assert_eq!(completed.len(), 3);
+        assert_eq!(completed[2].task_id, "task-3");
+        assert_eq!(completed[0].completed_at, "2024-01-31T09:00:00.000000Z");
+        assert!(completed[0].task.as_ref().unwrap().is_completed);
+        let requests = transport.requests();
+        assert_eq!(requests[0].url, "https://api.todoist.com/sync/v9/completed/get_all?annotate_items=true\
+            &project_id=2203306141&since=2024-01-01T00%3A00&limit=2");
+        assert!(requests[1].url.ends_with("&limit=2&offset=2"));
+    }
+
+    #[test]
+    fn activity_is_paginated_and_filtered_by_date() {
+        let event = |id: u64, date: &str| json!({
+            "id": id, "object_type": "item", "object_id": "2995104339", "event_type": "completed",
+            "event_date": date, "parent_project_id": "2203306141", "parent_item_id": null,
+            "initiator_id": null, "extra_data": {"content": "Buy milk"}
+        });
+        let transport = MockTransport::new();
+        transport.push_response(HttpResponse::json(200, &json!({"events": [
+            event(4, "2024-02-02T10:00:00Z"), event(3, "2024-01-31T09:00:00Z"),
+        ], "count": 2})));
+        transport.push_response(HttpResponse::json(200, &json!({"events": [
+            event(2, "2024-01-20T09:00:00Z"), event(1, "2023-12-31T23:00:00Z"),
+        ], "count": 2})));
+        let client = mock_client(&transport);
+
+        let query = activity::ActivityQuery {
+            object_type: Some(activity::ActivityObjectType::Task),
+            event_type: Some(activity::ActivityEventType::Completed),
+            since: Some("2024-01-01".to_string()),
+            until: Some("2024-02-01".to_string()),
+            limit: Some(2),
+            ..Default::default()
+        };
+        let events = activity::get_all_activity(&client, &query).unwrap();
+
+        let ids: Vec<&str> = events.iter().map(|event| event.id.as_str()).collect();
+        assert_eq!(ids, ["3", "2"]);
+        assert_eq!(events[0].object_type, activity::ActivityObjectType::Task);
+        assert_eq!(events[0].extra_data["content"], "Buy milk");
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].url, "https://api.todoist.com/sync/v9/activity/get?object_type=item\
+            &event_type=completed&limit=2");
+        assert!(requests[1].url.ends_with("&limit=2&offset=2"));
+    }
+
+    #[test]
+    fn activity_pages_are_capped() {
+        let event = |id: u32| json!({
+            "id": id, "object_type": "project", "object_id": "2203306141", "event_type": "updated",
+            "event_date": "2024-01-31T09:00:00Z", "parent_project_id": null, "parent_item_id": null,
+            "initiator_id": null, "extra_data": {}
+        });
+        let transport = MockTransport::new();
+        let full_page: Vec<Value> = (0..activity::ACTIVITY_PAGE_LIMIT).map(event).collect();
+        transport.push_response(HttpResponse::json(200, &json!({"events": full_page})));
+        transport.push_response(HttpResponse::json(200, &json!({"events": [event(100)]})));
+        let client = mock_client(&transport);
+
+        let query = activity::ActivityQuery { limit: Some(250), ..Default::default() };
+        let events = activity::get_all_activity(&client, &query).unwrap();
+
+        assert_eq!(events.len(), 101);
+        assert!(transport.requests()[1].url.ends_with("?limit=100&offset=100"));
+    }
+
+    #[test]
+    fn completed_task_pages_are_capped() {
+        let record = |id: u32| json!({
+            "id": id.to_string(), "task_id": format!("task-{}", id), "content": "Buy milk",
+            "project_id": "2203306141", "section_id": null, "completed_at": "2024-01-31T09:00:00.000000Z",
+        });
+        let transport = MockTransport::new();
+        let full_page: Vec<Value> = (0..tasks::COMPLETED_PAGE_LIMIT).map(record).collect();
+        transport.push_response(HttpResponse::json(200, &json!({"items": full_page})));
+        transport.push_response(HttpResponse::json(200, &json!({"items": [record(200)]})));
+        let client = mock_client(&transport);
+
+        let query = tasks::CompletedTasksQuery { limit: Some(500), ..Default::default() };
+        let completed = tasks::get_all_completed_tasks(&client, &query).unwrap();
+
+        assert_eq!(completed.len(), 201);
+        let requests = transport.requests();
+        assert!(requests[0].url.ends_with("&limit=200"));
+        assert!(requests[1].url.ends_with("&limit=200&offset=200"));
+    }
+
+    #[test]
+    fn project_lifecycle() {
+        let server = FakeTodoist::start();
+        let client = server.client();
+
+        let parent = projects::NewProject {
+            name: "Home".to_string(),
+            parent_id: None,
+            color: Some(Color::Magenta),
+            is_favorite: Some(true),
+            view_style: None,
+        }.upload(&client).unwrap();
+        let child = projects::NewProject {
+            name: "Garden".to_string(),
+            parent_id: Some(parent.id.clone()),
+            color: None,
+            is_favorite: None,
+            view_style: Some(projects::ViewStyle::Board),
+        }.upload(&client).unwrap();
+        assert_eq!(child.parent_id.as_deref(), Some(parent.id.as_str()));
+        assert!(matches!(child.view_style, projects::ViewStyle::Board));
+
+        // Only the given fields are changed
+        let updated = projects::UpdateProject {
+            name: None,
+            color: Some(Color::Teal),
+            is_favorite: None,
+            view_style: None,
+        }.upload(&client, &parent.id).unwrap();
+        assert_eq!(updated.name, "Home");
+        assert!(matches!(updated.color, Color::Teal));
+        assert!(updated.is_favorite);
+
+        let all = projects::get_projects(&client).unwrap();
+        assert_eq!(all.len(), 3);
+        assert!(all.iter().any(|p| p.is_inbox_project && p.id == server.inbox_id()));
+
+        // Deleting the parent deletes its child too
+        projects::delete_project_by_id(&client, &parent.id).unwrap();
+        assert_eq!(projects::get_projects(&client).unwrap().len(), 1);
+        let err = projects::get_project_by_id(&client, &child.id).unwrap_err();
+        assert!(matches!(err, TodoistAPIError::NotFound { resource, .. } if resource == "project"));
+    }
+
+    #[test]
+    fn section_lifecycle() {
+        let server = FakeTodoist::start();
+        let client = server.client();
+        let inbox = server.inbox_id();
+
+        let section = sections::NewSection {
+            name: "Errands".to_string(),
+            project_id: inbox.clone(),
+            order: None,
+        }.upload(&client).unwrap();
+        let mut new_task = tasks::NewTask::new("Post letter");
+        new_task.section_id = Some(section.id.clone());
+        let task = new_task.upload(&client).unwrap();
+        assert_eq!(task.project_id, inbox);
+
+        let renamed = sections::update_section_name(&client, &section.id, "Chores").unwrap();
+        assert_eq!(renamed.name, "Chores");
+        assert_eq!(sections::get_all_sections_in_project(&client, &inbox).unwrap().len(), 1);
+
+        // Deleting a section deletes the tasks in it
+        sections::delete_section_by_id(&client, &section.id).unwrap();
+        assert!(sections::get_all_sections(&client).unwrap().is_empty());
+        assert!(tasks::get_individual_task_by_id(&client, &task.id).is_err());
+    }
+
+    #[test]
+    fn task_lifecycle() {
+        let server = FakeTodoist::start();
+        let client = server.client();
+
+        let mut new_task = tasks::NewTask::new("Write report");
+        new_task.labels = Some(vec!["work".to_string()]);
+        new_task.due = Some(tasks::NewDue::Date { due_date: "2030-01-31".to_string() });
+        let task = new_task.upload(&client).unwrap();
+        assert_eq!(task.project_id, server.inbox_id());
+        assert_eq!(task.due.as_ref().unwrap().date, "2030-01-31");
+
+        let mut new_subtask = tasks::NewTask::new("Find sources");
+        new_subtask.parent_id = Some(task.id.clone());
+        let subtask = new_subtask.upload(&client).unwrap();
+
+        let updated = tasks::UpdateTask {
+            content: None,
+            description: Some("Quarterly".to_string()),
+            labels: None,
+            priority: Some(4),
+            due: None,
+            assignee_id: None,
+            duration: Some(tasks::NewDuration { duration: 90, duration_unit: tasks::DurationUnit::Minutes }),
+        }.upload(&client, &task.id).unwrap();
+        assert_eq!(updated.content, "Write report");
+        assert_eq!(updated.description, "Quarterly");
+        assert_eq!(updated.priority, 4);
+        assert_eq!(updated.duration.unwrap().amount, 90);
+        assert_eq!(tasks::get_active_tasks_by_label(&client, "work").unwrap().len(), 1);
+
+        // Closing a task closes its subtasks, but reopening it doesn't reopen them
+        tasks::close_task_by_id(&client, &task.id).unwrap();
+        assert!(tasks::get_all_active_tasks(&client).unwrap().is_empty());
+        tasks::reopen_task_by_id(&client, &task.id).unwrap();
+        let active = tasks::get_all_active_tasks(&client).unwrap();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].id, task.id);
+        assert!(tasks::get_individual_task_by_id(&client, &subtask.id).unwrap().is_completed);
+    }
+
+    #[test]
+    fn label_lifecycle() {
+        let server = FakeTodoist::start();
+        let client = server.client();
+
+        let label = labels::NewLabel {
+            name: "errand".to_string(),
+            order: None,
+            color: Some(Color::Red),
+            is_favorite: None,
+        }.upload(&client).unwrap();
+        let mut new_task = tasks::NewTask::new("Buy stamps");
+        new_task.labels = Some(vec!["errand".to_string(), "shared".to_string()]);
+        let task = new_task.upload(&client).unwrap();
+
+        // Renaming a personal label renames it on tasks too
+        let renamed = labels::UpdateLabel {
+            name: Some("errands".to_string()),
+            order: None,
+            color: None,
+            is_favorite: Some(true),
+        }.update(&client, &label.id).unwrap();
+        assert_eq!(renamed.name, "errands");
+        assert!(renamed.is_favorite);
+        assert_eq!(labels::get_all_shared_labels(&client).unwrap(), vec!["errands", "shared"]);
+
+        labels::rename_shared_label(&client, "shared", "family").unwrap();
+        assert_eq!(tasks::get_individual_task_by_id(&client, &task.id).unwrap().labels, vec!["errands", "family"]);
+        labels::remove_shared_label(&client, "family").unwrap();
+        labels::delete_label_by_id(&client, &label.id).unwrap();
+        assert!(tasks::get_individual_task_by_id(&client, &task.id).unwrap().labels.is_empty());
+        assert!(labels::get_all_personal_labels(&client).unwrap().is_empty());
+    }
+
+    #[test]
+    fn comment_lifecycle() {
+        let server = FakeTodoist::start();
+        let client = server.client();
+        let task = tasks::NewTask::new("Plan trip").upload(&client).unwrap();
+
+        let comment = comments::NewComment::on_task(&task.id, "Book trains").upload(&client).unwrap();
+        let id = comment.id.as_str();
+        comments::NewComment::on_project(&server.inbox_id(), "Packing list").upload(&client).unwrap();
+
+        assert_eq!(comments::get_comments_in_task(&client, &task.id).unwrap().len(), 1);
+        assert_eq!(tasks::get_individual_task_by_id(&client, &task.id).unwrap().comment_count, 1);
+        comments::update_comment_content(&client, id, "Book flights").unwrap();
+        comments::delete_comment_by_id(&client, id).unwrap();
+        assert!(comments::get_comments_in_task(&client, &task.id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn uploaded_file_is_attached_to_comment() {
+        let path = std::env::temp_dir().join(format!("todoist-upload-{}.txt", std::process::id()));
+        std::fs::write(&path, "Receipt").unwrap();
+        let transport = MockTransport::new();
+        transport.push_response(HttpResponse::json(200, &json!({
+            "file_name": path.file_name().unwrap().to_str().unwrap(), "file_size": 7,
+            "file_type": "text/plain", "file_url": "https://files.todoist.com/receipt.txt",
+            "resource_type": "file", "upload_state": "completed"
+        })));
+        transport.push_response(HttpResponse::json(200, &json!({
+            "id": "2992679862", "task_id": "2995104339", "project_id": null, "content": "Receipt",
+            "posted_at": "2016-09-22T07:00:00.000000Z", "attachment": null
+        })));
+        let client = mock_client(&transport);
+
+        let attachment = comments::upload_file(&client, &path);
+        std::fs::remove_file(&path).unwrap();
+        let mut comment = comments::NewComment::on_task("2995104339", "Receipt");
+        comment.attachment = Some(attachment.unwrap());
+        comment.upload(&client).unwrap();
+
+        let requests = transport.requests();
+        assert_eq!(requests[0].url, "https://api.todoist.com/sync/v9/uploads/add");
+        assert_eq!(requests[0].header("Content-Type"),
+                   Some("multipart/form-data; boundary=todoist-v2-rest-boundary"));
+        let body = String::from_utf8(requests[0].body.clone().unwrap()).unwrap();
+        assert!(body.contains("name=\"file\"; filename=\"todoist-upload-"));
+        assert!(body.contains("\r\n\r\nReceipt\r\n--todoist-v2-rest-boundary--"));
+        assert_eq!(requests[1].json::<Value>().unwrap()["attachment"], json!({
+            "resource_type": "file", "file_name": path.file_name().unwrap().to_str().unwrap(),
+            "file_size": 7, "file_type": "text/plain",
+            "file_url": "https://files.todoist.com/receipt.txt", "upload_state": "completed"
+        }));
+
+        let err = comments::upload_file(&client, "/nonexistent/receipt.pdf").unwrap_err();
+        assert!(matches!(err, TodoistAPIError::FileError(_)));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn file_is_uploaded_asynchronously() {
+        let path = std::env::temp_dir().join(format!("todoist-upload-async-{}.txt", std::process::id()));
+        std::fs::write(&path, "Receipt").unwrap();
+        let transport = MockTransport::new();
+        transport.push_response(HttpResponse::json(200, &json!({
+            "file_name": path.file_name().unwrap().to_str().unwrap(), "file_size": 7,
+            "file_type": "text/plain", "file_url": "https://files.todoist.com/receipt.txt",
+            "resource_type": "file", "upload_state": "completed"
+        })));
+        let client = TodoistClient::builder("0123456789abcdef")
+            .async_transport(transport.clone())
+            .build_async()
+            .unwrap();
+
+        let attachment = comments::upload_file_async(&client, &path).await;
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(attachment.unwrap().file_size, 7);
+        let body = String::from_utf8(transport.requests()[0].body.clone().unwrap()).unwrap();
+        assert!(body.contains("\r\n\r\nReceipt\r\n--todoist-v2-rest-boundary--"));
 
+        let err = comments::upload_file_async(&client, "/nonexistent/receipt.pdf").await.unwrap_err();
+        assert!(matches!(err, TodoistAPIError::FileError(_)));
+    }
+
+    #[test]
+    fn comments_round_trip_through_json() {
+        let json = json!({
+            "id": "2992679862", "task_id": null, "project_id": "2203306141",
+            "posted_at": "2016-09-22T07:00:00.000000Z", "content": "Floor plan",
+            "attachment": {
+                "resource_type": "file", "file_name": "plan.png", "file_size": 1024,
+                "file_type": "image/png", "file_url": "https://files.todoist.com/plan.png",
+                "upload_state": "completed",
+                "tn_l": ["https://files.todoist.com/l.png", 528, 528],
+                "tn_m": ["https://files.todoist.com/m.png", 247, 247],
+                "tn_s": ["https://files.todoist.com/s.png", 99, 99]
+            }
+        });
+
+        let comment: comments::Comment = serde_json::from_value(json.clone()).unwrap();
+
+        assert_eq!(comment.project_id.as_deref(), Some("2203306141"));
+        let attachment = comment.attachment.as_ref().unwrap();
+        assert_eq!(attachment.upload_state, Some(comments::UploadState::Completed));
+        match &attachment.attachment_type {
+            comments::AttachmentType::Image { tn_s, .. } => assert_eq!(tn_s.1, 99),
+            other => panic!("Expected an image, got {:?}", other),
+        }
+        assert_eq!(serde_json::to_value(&comment).unwrap(), json);
+    }
+
+    #[test]
+    fn tasks_move_with_their_subtasks() {
+        let server = FakeTodoist::start();
+        let client = server.client();
+        let project = projects::NewProject {
+            name: "Garden".to_string(),
+            parent_id: None,
+            color: None,
+            is_favorite: None,
+            view_style: None,
+        }.upload(&client).unwrap();
+        let section = sections::NewSection {
+            name: "Spring".to_string(),
+            project_id: project.id.clone(),
+            order: None,
+        }.upload(&client).unwrap();
+        let task = tasks::NewTask::new("Plant bulbs").upload(&client).unwrap();
+        let mut subtask = tasks::NewTask::new("Buy bulbs");
+        subtask.parent_id = Some(task.id.clone());
+        let subtask = subtask.upload(&client).unwrap();
+
+        let moved = tasks::move_task(&client, &task.id, &tasks::TaskDestination::Section(section.id.clone())).unwrap();
+        assert_eq!((moved.project_id.as_str(), moved.section_id.as_deref()), (project.id.as_str(), Some(section.id.as_str())));
+        let subtask = tasks::get_individual_task_by_id(&client, &subtask.id).unwrap();
+        assert_eq!(subtask.project_id, project.id);
+
+        let other = tasks::NewTask::new("Mow lawn").upload(&client).unwrap();
+        let moved = tasks::move_task(&client, &other.id, &tasks::TaskDestination::Parent(task.id.clone())).unwrap();
+        assert_eq!(moved.parent_id.as_deref(), Some(task.id.as_str()));
+        assert_eq!(moved.section_id.as_deref(), Some(section.id.as_str()));
+
+        let err = tasks::move_task(&client, &task.id, &tasks::TaskDestination::Project("404".to_string())).unwrap_err();
+        assert!(matches!(err, TodoistAPIError::CommandError(sync::CommandError { error_code: 21, .. })));
+    }
+
+    #[test]
+    fn projects_are_archived_and_moved() {
+        let server = FakeTodoist::start();
+        let client = server.client();
+        let new_project = |name: &str, parent_id: Option<String>| projects::NewProject {
+            name: name.to_string(),
+            parent_id,
+            color: None,
+            is_favorite: None,
+            view_style: None,
+        }.upload(&client).unwrap();
+        let work = new_project("Work", None);
+        let old = new_project("Old clients", Some(work.id.clone()));
+
+        let moved = projects::move_project(&client, &old.id, None).unwrap();
+        assert_eq!(moved.parent_id, None);
+        let moved = projects::move_project(&client, &old.id, Some(&work.id)).unwrap();
+        assert_eq!(moved.parent_id.as_deref(), Some(work.id.as_str()));
+        let err = projects::move_project(&client, &work.id, Some(&old.id)).unwrap_err();
+        assert!(matches!(err, TodoistAPIError::CommandError(_)));
+
+        projects::archive_project(&client, &work.id).unwrap();
+        assert_eq!(projects::get_projects(&client).unwrap().len(), 1);
+        let archived = projects::get_archived_projects(&client).unwrap();
+        assert_eq!(archived.len(), 2);
+        assert!(archived.iter().any(|project| project.name == "Old clients"));
+
+        let work = projects::unarchive_project(&client, &work.id).unwrap();
+        assert_eq!(work.name, "Work");
+        assert!(projects::get_archived_projects(&client).unwrap().is_empty());
+    }
+
+    #[test]
+    fn tasks_are_assigned_to_collaborators() {
+        let server = FakeTodoist::start();
+        let client = server.client();
+        let task = tasks::NewTask::new("Water plants").upload(&client).unwrap();
+
+        let collaborators = projects::get_project_collaborators(&client, &server.inbox_id()).unwrap();
+        assert_eq!(collaborators[0].id, crate::testing::FAKE_USER_ID);
+
+        let email = crate::testing::FAKE_USER_EMAIL.to_uppercase();
+        let assigned = tasks::assign_task_by_email(&client, &task.id, &email).unwrap();
+        assert_eq!(assigned.assignee_id.as_deref(), Some(crate::testing::FAKE_USER_ID));
+        tasks::assign_task_by_name(&client, &task.id, crate::testing::FAKE_USER_NAME).unwrap();
+
+        let err = tasks::assign_task_by_name(&client, &task.id, "Nobody").unwrap_err();
+        match err {
+            TodoistAPIError::CollaboratorNotFound { project_id, person } => {
+                assert_eq!((project_id, person), (server.inbox_id(), "Nobody".to_string()));
+            }
+            other => panic!("Expected CollaboratorNotFound, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn filter_lifecycle() {
+        let server = FakeTodoist::start();
+        let client = server.client();
+        let new_filter = |name: &str, query: &str| filters::NewFilter {
+            name: name.to_string(),
+            query: query.to_string(),
+            order: None,
+            color: None,
+            is_favorite: None,
+        }.upload(&client).unwrap();
+        let urgent = new_filter("Urgent", "p1 & today");
+        let waiting = new_filter("Waiting", "@waiting");
+        assert_eq!((urgent.order, waiting.order), (1, 2));
+
+        let updated = filters::UpdateFilter {
+            name: None,
+            query: Some("p1 & (today | overdue)".to_string()),
+            order: None,
+            color: Some(Color::Red),
+            is_favorite: Some(true),
+        }.update(&client, &urgent.id).unwrap();
+        assert_eq!(updated.name, "Urgent");
+        assert_eq!(updated.query, "p1 & (today | overdue)");
+        assert!(updated.is_favorite);
+
+        filters::reorder_filters(&client, &[&waiting.id, &urgent.id]).unwrap();
+        let names: Vec<String> = filters::get_all_filters(&client).unwrap().into_iter()
+            .filter(|filter| filter.order == 1)
+            .map(|filter| filter.name)
+            .collect();
+        assert_eq!(names, ["Waiting"]);
+
+        // Reordering some of the filters leaves the others where they are
+        let today = new_filter("Today", "today");
+        let later = new_filter("Later", "no date");
+        filters::reorder_filters(&client, &[&later.id, &waiting.id]).unwrap();
+        let mut all = filters::get_all_filters(&client).unwrap();
+        all.sort_by_key(|filter| filter.order);
+        let names: Vec<String> = all.into_iter().map(|filter| filter.name).collect();
+        assert_eq!(names, ["Later", "Urgent", "Today", "Waiting"]);
+        filters::delete_filter_by_id(&client, &today.id).unwrap();
+        filters::delete_filter_by_id(&client, &later.id).unwrap();
+
+        filters::delete_filter_by_id(&client, &urgent.id).unwrap();
+        assert_eq!(filters::get_all_filters(&client).unwrap().len(), 1);
+        let err = filters::get_filter_by_id(&client, &urgent.id).unwrap_err();
+        assert!(matches!(err, TodoistAPIError::NotFound { .. }));
+        let err = filters::delete_filter_by_id(&client, &urgent.id).unwrap_err();
+        assert!(matches!(err, TodoistAPIError::CommandError(_)));
+    }
+
+    #[test]
+    fn reminder_lifecycle() {
+        use reminders::{LocationTrigger, ReminderKind};
+        let server = FakeTodoist::start();
+        let client = server.client();
+        let task = tasks::NewTask::new("Catch train").upload(&client).unwrap();
+        let other = tasks::NewTask::new("Buy milk").upload(&client).unwrap();
+        let new_reminder = |task_id: &str, kind: ReminderKind| reminders::NewReminder {
+            task_id: task_id.to_string(),
+            kind,
+            notify_uid: None,
+        }.upload(&client).unwrap();
+
+        let relative = new_reminder(&task.id, ReminderKind::Relative { minutes_before: 30 });
+        assert_eq!(relative.notify_uid, crate::testing::FAKE_USER_ID);
+        let shop = ReminderKind::Location {
+            name: "Corner shop".to_string(),
+            latitude: 51.5074,
+            longitude: -0.1278,
+            trigger: LocationTrigger::OnEnter,
+            radius: Some(100),
+        };
+        let location = new_reminder(&other.id, shop.clone());
+        assert_eq!(location.kind, shop);
+
+        let absolute = ReminderKind::Absolute { datetime: "2024-01-31T09:00:00Z".to_string() };
+        let updated = reminders::UpdateReminder { kind: Some(absolute.clone()), ..Default::default() }
+            .update(&client, &relative.id)
+            .unwrap();
+        assert_eq!(updated.kind, absolute);
+        let on_task = reminders::get_task_reminders(&client, &task.id).unwrap();
+        assert_eq!(on_task, [updated]);
+
+        reminders::delete_reminder_by_id(&client, &relative.id).unwrap();
+        assert!(reminders::get_task_reminders(&client, &task.id).unwrap().is_empty());
+        assert_eq!(reminders::get_all_reminders(&client).unwrap(), [location]);
+
+        let err = reminders::NewReminder {
+            task_id: "404".to_string(),
+            kind: ReminderKind::Relative { minutes_before: 10 },
+            notify_uid: None,
+        }.upload(&client).unwrap_err();
+        assert!(matches!(err, TodoistAPIError::CommandError(_)));
+    }
+
+    #[test]
+    fn updated_reminder_must_be_returned() {
+        let transport = MockTransport::new();
+        let uuid = uuid::Uuid::new_v5(&uuid::Uuid::NAMESPACE_OID, b"update-reminder/uuid/0").to_string();
+        transport.push_response(HttpResponse::json(200, &json!({"sync_status": {uuid: "ok"}})));
+        transport.push_response(HttpResponse::json(200, &json!({"reminders": [], "sync_token": "abc"})));
+        let client = mock_client(&transport).with_request_id("update-reminder");
+
+        let err = reminders::UpdateReminder::default().update(&client, "2992679862").unwrap_err();
+
+        assert!(matches!(
+            err,
+            TodoistAPIError::ObjectNotReturned { resource, id } if resource == "reminder" && id == "2992679862"
+        ));
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[test]
+    fn user_profile_checks_the_token() {
+        let server = FakeTodoist::start();
+        let profile = user::get_user_profile(&server.client()).unwrap();
+        assert_eq!(profile.id, crate::testing::FAKE_USER_ID);
+        assert_eq!(profile.email, crate::testing::FAKE_USER_EMAIL);
+        assert_eq!(profile.timezone, "UTC");
+
+        let stranger = TodoistClient::builder("wrong-token").base_url(server.url()).build().unwrap();
+        let err = user::get_user_profile(&stranger).unwrap_err();
+        assert!(matches!(err, TodoistAPIError::Unauthorized { .. }));
+    }
+
+    #[test]
+    fn productivity_stats_are_parsed() {
+        let streak = |count: u32, start: &str, end: &str| json!({"count": count, "start": start, "end": end});
+        let transport = MockTransport::new();
+        transport.push_response(HttpResponse::json(200, &json!({
+            "karma_last_update": 50.0, "karma_trend": "up", "karma": 3120.0, "completed_count": 412,
+            "days_items": [{"date": "2024-01-31", "total_completed": 4, "items": []}],
+            "week_items": [{"from": "2024-01-29", "to": "2024-02-04", "total_completed": 11, "items": []}],
+            "karma_update_reasons": [],
+            "goals": {
+                "daily_goal": 5, "weekly_goal": 25, "karma_disabled": 0, "vacation_mode": 0,
+                "current_daily_streak": streak(0, "", ""),
+                "current_weekly_streak": streak(2, "2024-01-22", "2024-02-04"),
+                "max_daily_streak": streak(9, "2023-11-01", "2023-11-09"),
+                "max_weekly_streak": streak(6, "2023-09-04", "2023-10-15"),
+            },
+        })));
+        let client = mock_client(&transport);
+
+        let stats = user::get_productivity_stats(&client).unwrap();
+        assert_eq!(stats.completed_count, 412);
+        assert_eq!(stats.days[0].total_completed, 4);
+        assert_eq!(stats.weeks[0].from, "2024-01-29");
+        assert_eq!(stats.goals.max_daily_streak.count, 9);
+        assert_eq!(transport.requests()[0].url, "https://api.todoist.com/sync/v9/completed/get_stats");
+    }
+
+    #[test]
+    fn quick_add_parses_the_text() {
+        let server = FakeTodoist::start();
+        let client = server.client();
+        let home = projects::NewProject {
+            name: "Home".to_string(),
+            parent_id: None,
+            color: None,
+            is_favorite: None,
+            view_style: None,
+        }.upload(&client).unwrap();
+
+        let options = tasks::QuickAddOptions {
+            note: Some("Standing order failed last month".to_string()),
+            ..Default::default()
+        };
+        let task = tasks::quick_add(&client, "Pay rent #Home @finance p1", &options).unwrap();
+        assert_eq!(task.content, "Pay rent");
+        assert_eq!(task.project_id, home.id);
+        assert_eq!(task.labels, ["finance"]);
+        assert_eq!(task.priority, 4);
+        let comments = comments::get_comments_in_task(&client, &task.id).unwrap();
+        assert_eq!(comments[0].content, "Standing order failed last month");
+    }
+
+    #[test]
+    fn fake_server_rejects_bad_requests() {
+        let server = FakeTodoist::start();
+
+        let stranger = TodoistClient::builder("wrong-token").base_url(server.url()).build().unwrap();
+        let err = projects::get_projects(&stranger).unwrap_err();
+        assert!(matches!(err, TodoistAPIError::Unauthorized { .. }));
+
+        let mut new_task = tasks::NewTask::new("Lost");
+        new_task.project_id = Some("404".to_string());
+        let err = new_task.upload(&server.client()).unwrap_err();
+        assert!(matches!(err, TodoistAPIError::BadRequest { .. }));
+    }
+
+    #[test]
+    fn fake_server_ignores_repeated_request_ids() {
+        let server = FakeTodoist::start();
+        let client = server.client().with_request_id("create-once");
+
+        let first = tasks::NewTask::new("Only once").upload(&client).unwrap();
+        let second = tasks::NewTask::new("Only once").upload(&client).unwrap();
+
+        assert_eq!(first.id, second.id);
+        assert_eq!(tasks::get_all_active_tasks(&server.client()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn pinned_request_id_covers_a_write_then_a_sync_read() {
+        let server = FakeTodoist::start();
+        let task = tasks::NewTask::new("Catch train").upload(&server.client()).unwrap();
+        let new_reminder = reminders::NewReminder {
+            task_id: task.id.clone(),
+            kind: reminders::ReminderKind::Relative { minutes_before: 30 },
+            notify_uid: None,
+        };
+
+        let client = server.client().with_request_id("remind-once");
+        let first = new_reminder.upload(&client).unwrap();
+        let second = new_reminder.upload(&client).unwrap();
+
+        assert_eq!(first.id, second.id);
+        assert_eq!(reminders::get_all_reminders(&client).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn pinned_request_id_is_split_between_command_chunks() {
+        let server = FakeTodoist::start();
+        let client = server.client().with_request_id("many-filters");
+        let mut batch = sync::CommandBatch::new();
+        for number in 0..sync::COMMAND_LIMIT + 50 {
+            batch.add_filter(&filters::NewFilter {
+                name: format!("Filter {}", number),
+                query: "today".to_string(),
+                order: None,
+                color: None,
+                is_favorite: None,
+            });
+        }
+
+        let results = batch.submit(&client).unwrap();
+        assert!(results.is_ok());
+        assert_eq!(results.temp_id_mapping.len(), sync::COMMAND_LIMIT + 50);
+        assert_eq!(filters::get_all_filters(&client).unwrap().len(), sync::COMMAND_LIMIT + 50);
+
+        // Repeating the call applies nothing twice, but still reports each command's result
+        let repeated = batch.submit(&client).unwrap();
+        assert!(repeated.is_ok());
+        assert_eq!(repeated.temp_id_mapping, results.temp_id_mapping);
+        assert_eq!(filters::get_all_filters(&client).unwrap().len(), sync::COMMAND_LIMIT + 50);
+    }
+
+    #[test]
+    fn recorded_cassette_replays_offline() {
+        let transport = MockTransport::new();
+        transport.push_response(HttpResponse::json(200, &json!([task_json("1", "Buy milk")])));
+        transport.push_response(HttpResponse::json(200, &json!([])));
+        let recorder = RecordingTransport::new(transport);
+        let recording_client = TodoistClient::builder("0123456789abcdef")
+            .transport(recorder.clone())
+            .build()
+            .unwrap();
+        tasks::get_all_active_tasks(&recording_client).unwrap();
+        comments::get_comments_in_task(&recording_client, "1").unwrap();
+
+        let path = std::env::temp_dir().join(format!("todoist-cassette-{}.json", std::process::id()));
+        recorder.save(&path).unwrap();
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert!(!saved.contains("0123456789abcdef"));
+        assert!(saved.contains("Bearer [REDACTED]"));
+
+        // Replaying doesn't depend on the order of the requests, or the token used
+        let replay = ReplayTransport::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let client = TodoistClient::builder("another-token")
+            .transport(replay.clone())
+            .retry_policy(RetryPolicy::none())
+            .build()
+            .unwrap();
+        assert!(comments::get_comments_in_task(&client, "1").unwrap().is_empty());
+        assert_eq!(tasks::get_all_active_tasks(&client).unwrap()[0].content, "Buy milk");
+        assert_eq!(replay.remaining(), 0);
+        assert!(matches!(
+            tasks::get_all_active_tasks(&client).unwrap_err(),
+            TodoistAPIError::TransportError(_)
+        ));
+    }
+
+    #[test]
+    fn replay_matches_request_bodies() {
+        let server = FakeTodoist::start();
+        let recorder = RecordingTransport::new(ReqwestTransport::new(reqwest::blocking::Client::new()));
+        let recording_client = TodoistClient::builder(crate::testing::FAKE_API_TOKEN)
+            .base_url(server.url())
+            .transport(recorder.clone())
+            .build()
+            .unwrap();
+        tasks::NewTask::new("First").upload(&recording_client).unwrap();
+        tasks::NewTask::new("Second").upload(&recording_client).unwrap();
+        drop(server);
+
+        let client = TodoistClient::builder("another-token")
+            .base_url(recorder.cassette().interactions[0].request.url.trim_end_matches("/rest/v2/tasks"))
+            .transport(ReplayTransport::new(recorder.cassette()))
+            .retry_policy(RetryPolicy::none())
+            .build()
+            .unwrap();
+        assert_eq!(tasks::NewTask::new("Second").upload(&client).unwrap().content, "Second");
+        assert_eq!(tasks::NewTask::new("First").upload(&client).unwrap().content, "First");
+        assert!(tasks::NewTask::new("Third").upload(&client).is_err());
+    }
 
+    #[test]
+    fn replay_sync_commands_with_a_pinned_request_id() {
+        let new_filter = |name: &str| {
+            let mut batch = sync::CommandBatch::new();
+            batch.add_filter(&filters::NewFilter {
+                name: name.to_string(),
+                query: "today".to_string(),
+                order: None,
+                color: None,
+                is_favorite: None,
+            });
+            batch
+        };
+        let server = FakeTodoist::start();
+        let recorder = RecordingTransport::new(ReqwestTransport::new(reqwest::blocking::Client::new()));
+        let recording_client = TodoistClient::builder(crate::testing::FAKE_API_TOKEN)
+            .base_url(server.url())
+            .transport(recorder.clone())
+            .build()
+            .unwrap();
+        new_filter("Today").submit(&recording_client.with_request_id("add-today")).unwrap();
+        new_filter("Today").submit(&recording_client).unwrap();
+
+        let client = TodoistClient::builder("another-token")
+            .base_url(server.url())
+            .transport(ReplayTransport::new(recorder.cassette()))
+            .retry_policy(RetryPolicy::none())
+            .build()
+            .unwrap();
+        drop(server);
+        assert!(new_filter("Today").submit(&client.with_request_id("add-today")).unwrap().is_ok());
+        // Without a pinned ID the command's uuid is new, so it doesn't match the recording
+        assert!(new_filter("Today").submit(&client).is_err());
+    }
+
+    #[test]
+    fn recorded_oauth_exchange_has_no_secrets() {
+        let server = FakeTodoist::start();
+        let recorder = RecordingTransport::new(ReqwestTransport::new(reqwest::blocking::Client::new()));
+        let recording_client = TodoistClient::builder(crate::testing::FAKE_API_TOKEN)
+            .base_url(server.url())
+            .transport(recorder.clone())
+            .build()
+            .unwrap();
+        let app = OAuthApp::new("my-client", "client-secret-value")
+            .oauth_base_url(server.url())
+            .unwrap()
+            .client(recording_client);
+        let token = app.exchange_code(FAKE_AUTHORIZATION_CODE).unwrap();
+        app.revoke_token(&token.access_token).unwrap();
+
+        let path = std::env::temp_dir().join(format!("todoist-oauth-cassette-{}.json", std::process::id()));
+        recorder.save(&path).unwrap();
+        let saved = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(recorder.cassette().interactions.len(), 2);
+        assert!(!saved.contains("client-secret-value"));
+        assert!(!saved.contains(FAKE_AUTHORIZATION_CODE));
+        assert!(!saved.contains(&token.access_token));
+        assert!(!saved.contains(crate::testing::FAKE_API_TOKEN));
+
+        // The exchange still replays, as its secrets are redacted before matching
+        let replay_client = TodoistClient::builder("another-token")
+            .base_url(server.url())
+            .transport(ReplayTransport::new(recorder.cassette()))
+            .retry_policy(RetryPolicy::none())
+            .build()
+            .unwrap();
+        drop(server);
+        let app = app.client(replay_client);
+        assert_eq!(app.exchange_code(FAKE_AUTHORIZATION_CODE).unwrap().access_token, "[REDACTED]");
+    }
+
+    /// Collects the name and value of every span field and event field recorded.
+    #[cfg(feature = "tracing")]
+    #[derive(Clone, Default)]
+    struct FieldRecorder {
+        fields: std::sync::Arc<std::sync::Mutex<Vec<(String, String)>>>,
+    }
+
+    #[cfg(feature = "tracing")]
+    impl FieldRecorder {
+        fn get(&self, name: &str) -> Option<String> {
+            let fields = self.fields.lock().unwrap();
+            fields.iter().rev().find(|(field, _)| field == name).map(|(_, value)| value.clone())
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    impl tracing::field::Visit for &FieldRecorder {
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.fields.lock().unwrap().push((field.name().to_string(), format!("{:?}", value)));
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    impl tracing::Subscriber for FieldRecorder {
+        fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            span.record(&mut &*self);
+            tracing::span::Id::from_u64(1)
+        }
+
+        fn record(&self, _span: &tracing::span::Id, values: &tracing::span::Record<'_>) {
+            values.record(&mut &*self);
+        }
+
+        fn record_follows_from(&self, _span: &tracing::span::Id, _follows: &tracing::span::Id) {}
+
+        fn event(&self, event: &tracing::Event<'_>) {
+            event.record(&mut &*self);
+        }
+
+        fn enter(&self, _span: &tracing::span::Id) {}
+
+        fn exit(&self, _span: &tracing::span::Id) {}
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn requests_are_traced_without_the_token() {
+        let transport = MockTransport::new();
+        transport.push_response(HttpResponse::new(503, b"Service Unavailable"));
+        transport.push_response(HttpResponse::json(200, &task_json("2995104339", "Buy milk")));
+        let client = mock_client(&transport);
+        let recorder = FieldRecorder::default();
+
+        tracing::subscriber::with_default(recorder.clone(), || {
+            tasks::get_individual_task_by_id(&client, "2995104339").unwrap();
+        });
+
+        assert_eq!(recorder.get("endpoint").as_deref(), Some("/rest/v2/tasks/{id}"));
+        assert_eq!(recorder.get("method").as_deref(), Some("GET"));
+        assert_eq!(recorder.get("status").as_deref(), Some("200"));
+        assert_eq!(recorder.get("retries").as_deref(), Some("1"));
+        assert!(recorder.get("latency_ms").is_some());
+        let size = serde_json::to_vec(&task_json("2995104339", "Buy milk")).unwrap().len();
+        assert_eq!(recorder.get("response_size"), Some(size.to_string()));
+        let fields = recorder.fields.lock().unwrap();
+        assert!(fields.iter().all(|(_, value)| !value.contains("0123456789abcdef")));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn async_task_round_trip() {
+        let server = FakeTodoist::start();
+        let client = server.async_client();
+        let project = projects::NewProject {
+            name: "Shopping".to_string(),
+            parent_id: None,
+            color: None,
+            is_favorite: None,
+            view_style: None,
+        }.upload_async(&client).await.unwrap();
+
+        let mut new_task = tasks::NewTask::new("Buy milk");
+        new_task.project_id = Some(project.id.clone());
+        let task = new_task.upload_async(&client).await.unwrap();
+        let fetched = tasks::get_individual_task_by_id_async(&client, &task.id).await.unwrap();
+        assert_eq!(fetched.content, "Buy milk");
+        assert_eq!(fetched.project_id, project.id);
+
+        tasks::close_task_by_id_async(&client, &task.id).await.unwrap();
+        assert!(tasks::get_active_tasks_by_project_async(&client, &project.id).await.unwrap().is_empty());
+
+        // Deleting the project deletes the task with it
+        projects::delete_project_by_id_async(&client, &project.id).await.unwrap();
+        let err = tasks::get_individual_task_by_id_async(&client, &task.id).await.unwrap_err();
+        assert!(matches!(err, TodoistAPIError::NotFound { .. }));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn async_command_batch() {
+        let server = FakeTodoist::start();
+        let client = server.async_client();
+        let mut batch = sync::CommandBatch::new();
+        for name in ["Today", "Tomorrow"] {
+            batch.add_filter(&filters::NewFilter {
+                name: name.to_string(),
+                query: name.to_lowercase(),
+                order: None,
+                color: None,
+                is_favorite: None,
+            });
+        }
+
+        let results = batch.submit_async(&client).await.unwrap();
+        assert!(results.is_ok());
+        assert_eq!(results.temp_id_mapping.len(), 2);
+        let names: Vec<String> = filters::get_all_filters_async(&client).await.unwrap()
+            .into_iter()
+            .map(|filter| filter.name)
+            .collect();
+        assert_eq!(names, ["Today", "Tomorrow"]);
+    }
+
+    #[test]
+    fn authorize_url_has_scopes_and_state() {
+        let app = OAuthApp::new("my-client", "secret");
+
+        let url = app.authorize_url(&[Scope::DataRead, Scope::ProjectDelete], "xyz 123");
+
+        assert_eq!(
+            url,
+            "https://todoist.com/oauth/authorize?client_id=my-client&scope=data%3Aread%2Cproject%3Adelete&state=xyz+123"
+        );
+    }
+
+    #[test]
+    fn oauth_base_url_must_be_valid() {
+        let app = OAuthApp::new("my-client", "secret");
+
+        let err = app.clone().oauth_base_url("not a url").err().unwrap();
+        assert!(matches!(err, TodoistAPIError::InvalidUrl(_)));
+
+        let err = app.clone().oauth_base_url("mailto:someone@example.com").err().unwrap();
+        assert!(matches!(err, TodoistAPIError::InvalidUrl(_)));
+
+        let url = app.clone().oauth_base_url("http://localhost:8080/").unwrap().authorize_url(&[Scope::TaskAdd], "s");
+        assert!(url.starts_with("http://localhost:8080/oauth/authorize?"));
+        let url = app.oauth_base_url("http://localhost:8080/todoist").unwrap().authorize_url(&[Scope::TaskAdd], "s");
+        assert!(url.starts_with("http://localhost:8080/todoist/oauth/authorize?"));
+    }
+
+    #[test]
+    fn access_token_debug_is_redacted() {
+        let server = FakeTodoist::start();
+        let app = OAuthApp::new("my-client", "secret")
+            .oauth_base_url(server.url())
+            .unwrap()
+            .client(server.client());
+        let token = app.exchange_code(FAKE_AUTHORIZATION_CODE).unwrap();
+
+        let debug = format!("{:?}", token);
+        assert!(!debug.contains(&token.access_token));
+        assert_eq!(debug, r#"AccessToken { access_token: "[REDACTED]", token_type: "Bearer" }"#);
+    }
+
+    #[test]
+    fn oauth_code_exchange_and_revocation() {
+        let server = FakeTodoist::start();
+        let app = OAuthApp::new("my-client", "secret")
+            .oauth_base_url(server.url())
+            .unwrap()
+            .client(server.client());
+
+        let err = app.exchange_code("stolen-code").unwrap_err();
+        assert!(matches!(err, TodoistAPIError::BadRequest { .. }));
+
+        let token = app.exchange_code(FAKE_AUTHORIZATION_CODE).unwrap();
+        assert_eq!(token.token_type, "Bearer");
+        let user = app.user(&token);
+        assert_eq!(projects::get_projects(&user).unwrap().len(), 1);
+
+        app.revoke_token(&token.access_token).unwrap();
+        let err = projects::get_projects(&user).unwrap_err();
+        assert!(matches!(err, TodoistAPIError::Unauthorized { .. }));
+        assert!(projects::get_projects(&server.client()).is_ok());
+    }
+
+    #[test]
+    fn oauth_requests_never_send_a_token() {
+        let transport = MockTransport::new();
+        transport.push_response(HttpResponse::json(200, &json!({"access_token": "abc", "token_type": "Bearer"})));
+        transport.push_response(HttpResponse::new(200, b""));
+        let app = OAuthApp::new("my-client", "secret").client(mock_client(&transport));
+
+        let token = app.exchange_code("the-code").unwrap();
+        app.revoke_token(&token.access_token).unwrap();
+
+        let requests = transport.requests();
+        assert_eq!(requests[0].url, "https://todoist.com/oauth/access_token");
+        assert_eq!(
+            requests[0].body.as_deref(),
+            Some(&b"client_id=my-client&client_secret=secret&code=the-code"[..])
+        );
+        assert_eq!(requests[1].url, "https://api.todoist.com/sync/v9/access_tokens/revoke");
+        assert!(requests.iter().all(|request| request.header("Authorization").is_none()));
+    }
+
+    #[test]
+    fn config_profiles() {
+        std::env::set_var("TODOIST_TEST_WORK_TOKEN", "work-token");
+        let config = Config::parse(r#"
+            default_profile = "personal"
+
+            [profiles.personal]
+            token = "personal-token"
+
+            [profiles.work]
+            token_env = "TODOIST_TEST_WORK_TOKEN"
+            base_url = "http://localhost:8080/"
+            timeout_secs = 5
+            default_project_id = "2203306141"
+
+            [profiles.broken]
+            token_env = "TODOIST_TEST_UNSET_TOKEN"
+        "#).unwrap();
+
+        assert_eq!(config.profile(Some("personal")).unwrap().token().unwrap(), "personal-token");
+        let work = config.profile(Some("work")).unwrap();
+        assert_eq!(work.token().unwrap(), "work-token");
+        assert_eq!(work.default_project_id.as_deref(), Some("2203306141"));
+        assert_eq!(work.user().unwrap().base_url(), "http://localhost:8080");
+        assert!(matches!(config.user(Some("broken")), Err(TodoistAPIError::ConfigError(_))));
+        assert!(matches!(config.profile(Some("missing")), Err(TodoistAPIError::ConfigError(_))));
+        assert!(Config::parse("[profiles.typo]\ntokn = \"abc\"").is_err());
+    }
+
+    #[test]
+    fn debug_output_redacts_tokens() {
+        let client = TodoistClient::new("0123456789abcdef");
+        let profile = Config::parse("[profiles.default]\ntoken = \"0123456789abcdef\"").unwrap();
+
+        for debug in [
+            format!("{:?}", client),
+            format!("{:?}", TodoistClient::builder("0123456789abcdef")),
+            format!("{:?}", profile),
+        ] {
+            assert!(!debug.contains("0123456789abcdef"), "{}", debug);
+            assert!(debug.contains("[REDACTED]"), "{}", debug);
+        }
+    }
 }
@@ -0,0 +1,220 @@
+//! Connecting users' own Todoist accounts through OAuth2, instead of using a personal API token.
+//!
+//! An app registered at <https://developer.todoist.com/appconsole.html> gets a client ID and a
+//! client secret. Connecting a user's account then takes three steps:
+//! 1. Send the user to the URL from `OAuthApp::authorize_url()`, asking for the scopes the app
+//!    needs. Include a random `state` (e.g. from `new_state()`), and remember it.
+//! 2. Todoist redirects the user back to the app's redirect URL, with `code` and `state` query
+//!    parameters. Check that the state is the one remembered, to guard against forged requests.
+//! 3. Exchange the code for an access token with `OAuthApp::exchange_code()`, and turn that into a
+//!    `TodoistUser` with `OAuthApp::user()`.
+//!
+//! ```no_run
+//! use todoist_v2_rest::{oauth, projects};
+//! use todoist_v2_rest::oauth::{OAuthApp, Scope};
+//! let app = OAuthApp::new("0123456789abcdef", "secret");
+//!
+//! let state = oauth::new_state();
+//! let url = app.authorize_url(&[Scope::DataReadWrite, Scope::DataDelete], &state);
+//! println!("Connect your Todoist account at {}", url);
+//!
+//! // Later, once Todoist has redirected the user back with a code
+//! let token = app.exchange_code("the code from the redirect").expect("Couldn't get access token");
+//! let user = app.user(&token);
+//! let user_projects = projects::get_projects(&user).expect("Couldn't load project list");
+//! ```
+//!
+//! An app sends its own requests through a `TodoistClient` with the default settings. To change
+//! them (e.g. to test against a local stand-in server, whose URL should also be given to
+//! `oauth_base_url()`), give it another client with `client()`; users from `user()` then share
+//! that client's settings.
+
+use std::fmt;
+
+use reqwest::{Method, Url};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::err::TodoistAPIError;
+use crate::general::{get_204_from_response, get_from_response, ApiRequest, REDACTED};
+use crate::{TodoistClient, TodoistUser};
+#[cfg(feature = "async")]
+use crate::AsyncTodoistClient;
+
+/// The base URL of Todoist's OAuth endpoints, used unless another is given.
+pub const DEFAULT_OAUTH_BASE_URL: &str = "https://todoist.com";
+
+
+/// A permission an app can ask the user for.
+///
+/// See <https://developer.todoist.com/guides/#oauth> for exactly what each allows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// Add tasks, but not read or change anything.
+    TaskAdd,
+    /// Read the user's data.
+    DataRead,
+    /// Read and change the user's data, except deleting it.
+    DataReadWrite,
+    /// Delete the user's tasks, labels and filters.
+    DataDelete,
+    /// Delete the user's projects.
+    ProjectDelete,
+}
+
+impl Scope {
+    /// The name of the scope, as used in the authorize URL.
+    pub fn to_str(&self) -> &str {
+        match self {
+            Scope::TaskAdd => "task:add",
+            Scope::DataRead => "data:read",
+            Scope::DataReadWrite => "data:read_write",
+            Scope::DataDelete => "data:delete",
+            Scope::ProjectDelete => "project:delete",
+        }
+    }
+}
+
+
+/// An access token returned by Todoist in exchange for an authorization code. Formatting it with
+/// `{:?}` leaves the token itself out.
+#[derive(Clone, Deserialize)]
+pub struct AccessToken {
+    /// The token, which is used in the same way as a personal API token.
+    pub access_token: String,
+
+    /// The type of the token, which is always "Bearer".
+    pub token_type: String,
+}
+
+impl fmt::Debug for AccessToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AccessToken")
+            .field("access_token", &REDACTED)
+            .field("token_type", &self.token_type)
+            .finish()
+    }
+}
+
+
+/// An app registered with Todoist, which users can connect their accounts to.
+#[derive(Clone)]
+pub struct OAuthApp {
+    client_id: String,
+    client_secret: String,
+    oauth_base_url: Url,
+    client: TodoistClient,
+}
+
+impl OAuthApp {
+    /// Create an app with the given client ID and client secret, as shown in Todoist's app
+    /// console.
+    pub fn new(client_id: &str, client_secret: &str) -> OAuthApp {
+        OAuthApp {
+            client_id: client_id.to_string(),
+            client_secret: client_secret.to_string(),
+            oauth_base_url: Url::parse(DEFAULT_OAUTH_BASE_URL).expect("Constant URL is valid"),
+            client: TodoistClient::new(""),
+        }
+    }
+
+    /// Use the OAuth endpoints at this base URL instead of Todoist's, e.g. `http://localhost:8080`.
+    /// Fails with `InvalidUrl` if the base URL can't be parsed, or can't have paths added to it.
+    pub fn oauth_base_url(mut self, base_url: &str) -> Result<OAuthApp, TodoistAPIError> {
+        let url = Url::parse(base_url).map_err(|err| TodoistAPIError::InvalidUrl(err.to_string()))?;
+        if url.cannot_be_a_base() {
+            return Err(TodoistAPIError::InvalidUrl(format!("{} can't be a base URL", base_url)));
+        }
+        self.oauth_base_url = url;
+        Ok(self)
+    }
+
+    /// Send the app's requests through this client instead of one with the default settings. The
+    /// client's own token is never sent.
+    pub fn client(mut self, client: TodoistClient) -> OAuthApp {
+        self.client = client;
+        self
+    }
+
+    /// The URL to send a user to, so that they can allow the app the given scopes. Todoist will
+    /// send them back to the app's redirect URL with the same `state`.
+    pub fn authorize_url(&self, scopes: &[Scope], state: &str) -> String {
+        let scope = scopes.iter().map(Scope::to_str).collect::<Vec<_>>().join(",");
+        let mut url = self.oauth_base_url.clone();
+        let path = format!("{}/oauth/authorize", url.path().trim_end_matches('/'));
+        url.set_path(&path);
+        url.set_query(None);
+        url.query_pairs_mut()
+            .append_pair("client_id", &self.client_id)
+            .append_pair("scope", &scope)
+            .append_pair("state", state);
+        url.to_string()
+    }
+
+    /// Exchange the code Todoist gave when redirecting the user back for an access token.
+    pub fn exchange_code(&self, code: &str) -> Result<AccessToken, TodoistAPIError> {
+        // Send the API request
+        let response = self.client.send(self.exchange_code_request(code));
+        get_from_response(response)
+    }
+
+    /// Asynchronous version of `exchange_code()`, sending the request through the given client
+    /// instead. The client's own token is never sent.
+    #[cfg(feature = "async")]
+    pub async fn exchange_code_async(&self, client: &AsyncTodoistClient, code: &str) -> Result<AccessToken, TodoistAPIError> {
+        let response = client.send(self.exchange_code_request(code)).await;
+        get_from_response(response)
+    }
+
+    /// Revoke an access token, so that it can no longer be used.
+    pub fn revoke_token(&self, access_token: &str) -> Result<(), TodoistAPIError> {
+        // Send the API request
+        let response = self.client.send(self.revoke_token_request(access_token)?);
+        get_204_from_response(response)
+    }
+
+    /// Asynchronous version of `revoke_token()`, sending the request through the given client
+    /// instead. The client's own token is never sent.
+    #[cfg(feature = "async")]
+    pub async fn revoke_token_async(&self, client: &AsyncTodoistClient, access_token: &str) -> Result<(), TodoistAPIError> {
+        let response = client.send(self.revoke_token_request(access_token)?).await;
+        get_204_from_response(response)
+    }
+
+    /// A user which makes requests with the access token, with the same settings as the app's
+    /// client.
+    pub fn user(&self, token: &AccessToken) -> TodoistUser {
+        self.client.with_token(&token.access_token)
+    }
+
+    /// Build the request for `exchange_code()` and its async version. It isn't retried, since a
+    /// code can only be exchanged once.
+    fn exchange_code_request(&self, code: &str) -> ApiRequest {
+        ApiRequest::new(Method::POST, "/oauth/access_token".to_string())
+            .base_url(self.oauth_base_url.as_str().trim_end_matches('/'))
+            .unauthenticated()
+            .form(&[
+                ("client_id", &self.client_id),
+                ("client_secret", &self.client_secret),
+                ("code", code),
+            ])
+    }
+
+    /// Build the request for `revoke_token()` and its async version.
+    fn revoke_token_request(&self, access_token: &str) -> Result<ApiRequest, TodoistAPIError> {
+        let body = json!({
+            "client_id": self.client_id,
+            "client_secret": self.client_secret,
+            "access_token": access_token,
+        });
+        ApiRequest::sync_post("/access_tokens/revoke")
+            .unauthenticated()
+            .json(&body)
+    }
+}
+
+
+/// Generate a random `state` for `OAuthApp::authorize_url()`.
+pub fn new_state() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
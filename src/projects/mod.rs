@@ -6,11 +6,20 @@
 //! - Create a new project (`project::NewProject::upload()`)
 //! - Update a project (`project::UpdateProject::upload()`)
 //! - Delete a project (`project::delete_project_by_id()`)
+//! - Get the collaborators on a shared project (`project::get_project_collaborators()`)
+//! - Archive or unarchive a project (`project::archive_project()` and
+//!   `project::unarchive_project()`), and list archived projects
+//!   (`project::get_archived_projects()`)
+//! - Move a project under another one, or to the top level (`project::move_project()`)
 //!
 //! The first returns a `Vec` of `Project` structs. The next three each deal with an individual
 //! project, and return a single `Project` struct representing the current state of that project
-//! after the API request has been made. The final one returns nothing. All functions can return
-//! a `TodoistAPIError`.
+//! after the API request has been made. Deleting returns nothing, and getting collaborators returns
+//! a `Vec` of `Collaborator` structs, whose IDs can be used as the `assignee_id` of a task. All
+//! functions can return a `TodoistAPIError`.
+//!
+//! The REST API can't archive or move projects, so those functions send commands through the
+//! Sync API instead. If Todoist rejects one, they return a `CommandError`.
 //!
 //! Creating a new project, or updating an existing one, is done by creating an instance of
 //! `NewProject` or `UpdateProject` respectively. Optional fields are represented by `Option` types.
@@ -21,15 +30,16 @@ mod structs;
 
 use crate::TodoistUser;
 use crate::err::TodoistAPIError;
-use crate::general::{get_from_reqwest_response, get_204_from_reqwest_response};
+use crate::general::{get_from_response, get_204_from_response, ApiRequest};
+use crate::sync::{CommandBatch, SyncProject};
+#[cfg(feature = "async")]
+use crate::AsyncTodoistClient;
 
 pub use crate::projects::structs::view_style::ViewStyle;
 pub use crate::projects::structs::project::Project;
 pub use crate::projects::structs::new_project::NewProject;
 pub use crate::projects::structs::update_project::UpdateProject;
-
-use reqwest;
-
+pub use crate::projects::structs::collaborator::Collaborator;
 
 
 /// Return a Vec of all the user's projects.
@@ -37,14 +47,17 @@ use reqwest;
 /// <https://developer.todoist.com/rest/v2/#get-all-projects>
 pub fn get_projects(user: &TodoistUser) -> Result<Vec<Project>, TodoistAPIError> {
     // Make the API request
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .get("https://api.todoist.com/rest/v2/projects")
-        .header("Authorization", String::from("Bearer ") + &user.token)
-        .send();
+    let response = user.send(ApiRequest::get("/projects"));
 
     // Now interpret this response properly
-    get_from_reqwest_response(response)
+    get_from_response(response)
+}
+
+/// Asynchronous version of `get_projects()`.
+#[cfg(feature = "async")]
+pub async fn get_projects_async(client: &AsyncTodoistClient) -> Result<Vec<Project>, TodoistAPIError> {
+    let response = client.send(ApiRequest::get("/projects")).await;
+    get_from_response(response)
 }
 
 
@@ -52,12 +65,37 @@ pub fn get_projects(user: &TodoistUser) -> Result<Vec<Project>, TodoistAPIError>
 ///
 /// <https://developer.todoist.com/rest/v2/#get-a-project>
 pub fn get_project_by_id(user: &TodoistUser, id: &str) -> Result<Project, TodoistAPIError> {
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .get(format!("https://api.todoist.com/rest/v2/projects/{}", id))
-        .header("Authorization", "Bearer ".to_string() + &user.token)
-        .send();
-    get_from_reqwest_response(response)
+    let response = user.send(ApiRequest::get(&format!("/projects/{}", id)).resource("project", id));
+    get_from_response(response)
+}
+
+/// Asynchronous version of `get_project_by_id()`.
+#[cfg(feature = "async")]
+pub async fn get_project_by_id_async(client: &AsyncTodoistClient, id: &str) -> Result<Project, TodoistAPIError> {
+    let request = ApiRequest::get(&format!("/projects/{}", id))
+        .resource("project", id);
+    let response = client.send(request).await;
+    get_from_response(response)
+}
+
+
+/// Get everyone who can be assigned tasks in the project with the given ID.
+///
+/// <https://developer.todoist.com/rest/v2/#get-all-collaborators>
+pub fn get_project_collaborators(user: &TodoistUser, project_id: &str) -> Result<Vec<Collaborator>, TodoistAPIError> {
+    let request = ApiRequest::get(&format!("/projects/{}/collaborators", project_id))
+        .resource("project", project_id);
+    let response = user.send(request);
+    get_from_response(response)
+}
+
+/// Asynchronous version of `get_project_collaborators()`.
+#[cfg(feature = "async")]
+pub async fn get_project_collaborators_async(client: &AsyncTodoistClient, project_id: &str) -> Result<Vec<Collaborator>, TodoistAPIError> {
+    let request = ApiRequest::get(&format!("/projects/{}/collaborators", project_id))
+        .resource("project", project_id);
+    let response = client.send(request).await;
+    get_from_response(response)
 }
 
 
@@ -67,10 +105,91 @@ pub fn get_project_by_id(user: &TodoistUser, id: &str) -> Result<Project, Todois
 ///
 /// <https://developer.todoist.com/rest/v2/#delete-a-project>
 pub fn delete_project_by_id(user: &TodoistUser, id: &str) -> Result<(), TodoistAPIError> {
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .delete(format!("https://api.todoist.com/rest/v2/projects/{}", id))
-        .header("Authorization", "Bearer ".to_string() + &user.token)
-        .send();
-    get_204_from_reqwest_response(response)
+    let request = ApiRequest::delete(&format!("/projects/{}", id))
+        .resource("project", id);
+    let response = user.send(request);
+    get_204_from_response(response)
+}
+
+/// Asynchronous version of `delete_project_by_id()`.
+#[cfg(feature = "async")]
+pub async fn delete_project_by_id_async(client: &AsyncTodoistClient, id: &str) -> Result<(), TodoistAPIError> {
+    let request = ApiRequest::delete(&format!("/projects/{}", id))
+        .resource("project", id);
+    let response = client.send(request).await;
+    get_204_from_response(response)
+}
+
+
+/// Archive a project, along with its descendants. Archived projects are left out of
+/// `get_projects()`, and can be listed with `get_archived_projects()`.
+pub fn archive_project(user: &TodoistUser, id: &str) -> Result<(), TodoistAPIError> {
+    let mut batch = CommandBatch::new();
+    batch.archive_project(id);
+    batch.submit(user)?.into_result()?;
+    Ok(())
+}
+
+/// Asynchronous version of `archive_project()`.
+#[cfg(feature = "async")]
+pub async fn archive_project_async(client: &AsyncTodoistClient, id: &str) -> Result<(), TodoistAPIError> {
+    let mut batch = CommandBatch::new();
+    batch.archive_project(id);
+    batch.submit_async(client).await?.into_result()?;
+    Ok(())
+}
+
+
+/// Unarchive a project, returning it as it is now.
+pub fn unarchive_project(user: &TodoistUser, id: &str) -> Result<Project, TodoistAPIError> {
+    let mut batch = CommandBatch::new();
+    batch.unarchive_project(id);
+    batch.submit(user)?.into_result()?;
+    get_project_by_id(user, id)
+}
+
+/// Asynchronous version of `unarchive_project()`.
+#[cfg(feature = "async")]
+pub async fn unarchive_project_async(client: &AsyncTodoistClient, id: &str) -> Result<Project, TodoistAPIError> {
+    let mut batch = CommandBatch::new();
+    batch.unarchive_project(id);
+    batch.submit_async(client).await?.into_result()?;
+    get_project_by_id_async(client, id).await
+}
+
+
+/// Return a Vec of the user's archived projects. As with projects from the `sync` module, their
+/// `comment_count` is always 0.
+pub fn get_archived_projects(user: &TodoistUser) -> Result<Vec<Project>, TodoistAPIError> {
+    // Send the API request
+    let response = user.send(ApiRequest::sync_get("/projects/get_archived"));
+    get_from_response::<Vec<SyncProject>>(response)
+        .map(|projects| projects.into_iter().map(Project::from).collect())
+}
+
+/// Asynchronous version of `get_archived_projects()`.
+#[cfg(feature = "async")]
+pub async fn get_archived_projects_async(client: &AsyncTodoistClient) -> Result<Vec<Project>, TodoistAPIError> {
+    let response = client.send(ApiRequest::sync_get("/projects/get_archived")).await;
+    get_from_response::<Vec<SyncProject>>(response)
+        .map(|projects| projects.into_iter().map(Project::from).collect())
+}
+
+
+/// Move a project, along with its descendants, under the project with ID `parent_id`, or to the
+/// top level if `parent_id` is `None`. Returns the moved project.
+pub fn move_project(user: &TodoistUser, id: &str, parent_id: Option<&str>) -> Result<Project, TodoistAPIError> {
+    let mut batch = CommandBatch::new();
+    batch.move_project(id, parent_id);
+    batch.submit(user)?.into_result()?;
+    get_project_by_id(user, id)
+}
+
+/// Asynchronous version of `move_project()`.
+#[cfg(feature = "async")]
+pub async fn move_project_async(client: &AsyncTodoistClient, id: &str, parent_id: Option<&str>) -> Result<Project, TodoistAPIError> {
+    let mut batch = CommandBatch::new();
+    batch.move_project(id, parent_id);
+    batch.submit_async(client).await?.into_result()?;
+    get_project_by_id_async(client, id).await
 }
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+/// A person who can be assigned tasks in a shared project, as returned by
+/// `get_project_collaborators()`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub struct Collaborator {
+    pub id: String,
+    pub name: String,
+    pub email: String,
+}
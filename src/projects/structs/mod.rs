@@ -2,3 +2,4 @@ pub mod view_style;
 pub mod project;
 pub mod new_project;
 pub mod update_project;
+pub mod collaborator;
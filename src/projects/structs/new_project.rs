@@ -1,9 +1,11 @@
 use serde::Serialize;
 use crate::color::Color;
 use crate::err::TodoistAPIError;
-use crate::general::get_from_reqwest_response;
+use crate::general::{get_from_response, ApiRequest};
 use crate::projects::{Project, ViewStyle};
 use crate::TodoistUser;
+#[cfg(feature = "async")]
+use crate::AsyncTodoistClient;
 
 /// Represents the creation of a new Todoist project.
 ///
@@ -38,14 +40,16 @@ impl NewProject {
     /// If successful, returns a full Project struct as returned by the API.
     pub fn upload(&self, user: &TodoistUser) -> Result<Project, TodoistAPIError> {
         // Make the API request
-        let client = reqwest::blocking::Client::new();
-        let response = client
-            .post("https://api.todoist.com/rest/v2/projects")
-            .header("Authorization", String::from("Bearer ") + &user.token)
-            .json(self)
-            .send();
+        let response = user.send(ApiRequest::post("/projects").json(self)?);
 
         // Now interpret this response properly
-        get_from_reqwest_response(response)
+        get_from_response(response)
+    }
+
+    /// Asynchronous version of `upload()`.
+    #[cfg(feature = "async")]
+    pub async fn upload_async(&self, client: &AsyncTodoistClient) -> Result<Project, TodoistAPIError> {
+        let response = client.send(ApiRequest::post("/projects").json(self)?).await;
+        get_from_response(response)
     }
 }
@@ -1,12 +1,15 @@
 use serde::Serialize;
 use crate::color::Color;
 use crate::err::TodoistAPIError;
-use crate::general::get_from_reqwest_response;
+use crate::general::{get_from_response, ApiRequest};
 use crate::projects::{Project, ViewStyle};
 use crate::TodoistUser;
+#[cfg(feature = "async")]
+use crate::AsyncTodoistClient;
 
 /// A struct representing an update to a project. Distinct from the `NewProject` struct as it is
-/// not possible after a project's creation to change its parent using the REST v2 API.
+/// not possible after a project's creation to change its parent using the REST v2 API; use
+/// `move_project()` for that instead.
 ///
 /// Construct an instance of this struct as appropriate, then call its `upload()` method, giving
 /// the ID of the project to update. If successful, returns a full `Project` struct as returned
@@ -16,7 +19,7 @@ use crate::TodoistUser;
 #[derive(Debug, Serialize)]
 pub struct UpdateProject {
     /// The name of the project. If unspecified, the name will not be changed.
-    /// #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
 
     /// The colour of the project. If unspecified, the colour will not be changed.
@@ -37,14 +40,22 @@ impl UpdateProject {
     /// Upload a new project with these attributes to the Todoist API.
     pub fn upload(&self, user: &TodoistUser, id: &str) -> Result<Project, TodoistAPIError> {
         // Make the API request
-        let client = reqwest::blocking::Client::new();
-        let response = client
-            .post(format!("https://api.todoist.com/rest/v2/projects/{}", id))
-            .header("Authorization", String::from("Bearer ") + &user.token)
-            .json(&self)
-            .send();
+        let request = ApiRequest::post(&format!("/projects/{}", id))
+            .resource("project", id)
+            .json(self)?;
+        let response = user.send(request);
 
         // Now interpret this response properly
-        get_from_reqwest_response(response)
+        get_from_response(response)
+    }
+
+    /// Asynchronous version of `upload()`.
+    #[cfg(feature = "async")]
+    pub async fn upload_async(&self, client: &AsyncTodoistClient, id: &str) -> Result<Project, TodoistAPIError> {
+        let request = ApiRequest::post(&format!("/projects/{}", id))
+            .resource("project", id)
+            .json(self)?;
+        let response = client.send(request).await;
+        get_from_response(response)
     }
 }
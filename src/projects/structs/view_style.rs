@@ -21,6 +21,7 @@ impl ViewStyle {
     }
 
     /// Convert an API string into a ViewStyle
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> ViewStyle {
         match s {
             "board" => ViewStyle::Board,
@@ -0,0 +1,146 @@
+//! A client-side rate limiter, to avoid exceeding Todoist's request quota.
+//!
+//! Todoist limits each user to a certain number of requests within a rolling window (at the time
+//! of writing, 450 requests per 15 minutes for the REST API), and responds with
+//! `429 Too Many Requests` once that is exceeded. Giving a `RateLimiter` to the client builder
+//! makes every request wait until there is budget for it, rather than being rejected.
+//!
+//! The limiter is a token bucket: it starts full with `quota` tokens, each request takes one, and
+//! tokens are refilled steadily so that a full bucket's worth is restored over each `window`.
+//! Cloning a `RateLimiter` gives a handle to the same bucket, so one limiter can be shared between
+//! several clients (and threads) which use the same account.
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use todoist_v2_rest::TodoistClient;
+//! use todoist_v2_rest::rate_limit::RateLimiter;
+//! let limiter = RateLimiter::new(450, Duration::from_secs(15 * 60));
+//! let client = TodoistClient::builder("a2a72c2f394b265bb798d5dc4ef55be51443d519")
+//!     .rate_limiter(limiter.clone())
+//!     .build()
+//!     .expect("Couldn't build client");
+//! println!("{} requests left", limiter.remaining());
+//! ```
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::err::TodoistAPIError;
+
+
+/// A token bucket shared by every clone, which paces requests to stay within a quota.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    quota: u32,
+    window: Duration,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+/// The mutable state of a `RateLimiter`.
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    /// Create a limiter which allows `quota` requests in every `window`. It starts with the full
+    /// quota available.
+    ///
+    /// Panics if the quota or the window is zero, as the limiter would never let a request
+    /// through. Use `try_new()` to get an error instead.
+    pub fn new(quota: u32, window: Duration) -> RateLimiter {
+        RateLimiter::try_new(quota, window).expect("Rate limiter quota and window must be non-zero")
+    }
+
+    /// Create a limiter which allows `quota` requests in every `window`, or fail with
+    /// `InvalidRateLimiter` if the quota or the window is zero.
+    pub fn try_new(quota: u32, window: Duration) -> Result<RateLimiter, TodoistAPIError> {
+        if quota == 0 {
+            return Err(TodoistAPIError::InvalidRateLimiter("the quota is zero".to_string()));
+        }
+        if window.is_zero() {
+            return Err(TodoistAPIError::InvalidRateLimiter("the window is zero".to_string()));
+        }
+        Ok(RateLimiter {
+            quota,
+            window,
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: quota as f64,
+                last_refill: Instant::now(),
+            })),
+        })
+    }
+
+    /// The number of requests allowed in each window.
+    pub fn quota(&self) -> u32 {
+        self.quota
+    }
+
+    /// The length of the window the quota applies to.
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// The number of requests which can be made right now without waiting.
+    pub fn remaining(&self) -> u32 {
+        let mut bucket = self.lock();
+        self.refill(&mut bucket);
+        bucket.tokens.floor() as u32
+    }
+
+    /// Take budget for one request if there is any, returning whether it was taken.
+    pub fn try_acquire(&self) -> bool {
+        self.reserve().is_none()
+    }
+
+    /// Wait until there is budget for one request, then take it.
+    pub fn acquire(&self) {
+        while let Some(wait) = self.reserve() {
+            std::thread::sleep(wait);
+        }
+    }
+
+    /// The asynchronous version of `acquire()`.
+    #[cfg(feature = "async")]
+    pub async fn acquire_async(&self) {
+        while let Some(wait) = self.reserve() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Take budget for one request if there is any, returning `None`. Otherwise return how long it
+    /// will be until there is.
+    fn reserve(&self) -> Option<Duration> {
+        let mut bucket = self.lock();
+        self.refill(&mut bucket);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return None;
+        }
+        let missing = 1.0 - bucket.tokens;
+        Some(self.window.mul_f64(missing / self.quota as f64))
+    }
+
+    /// Add the tokens accrued since the bucket was last refilled.
+    fn refill(&self, bucket: &mut Bucket) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        let rate = self.quota as f64 / self.window.as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(self.quota as f64);
+        bucket.last_refill = now;
+    }
+
+    /// Lock the bucket. A panic while it was locked can't leave it inconsistent, so a poisoned
+    /// lock is just used as normal.
+    fn lock(&self) -> std::sync::MutexGuard<'_, Bucket> {
+        self.bucket.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for RateLimiter {
+    /// Todoist's REST API quota of 450 requests per 15 minutes.
+    fn default() -> RateLimiter {
+        RateLimiter::new(450, Duration::from_secs(15 * 60))
+    }
+}
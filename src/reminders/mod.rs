@@ -0,0 +1,95 @@
+//! Functions for working with reminders on tasks.
+//!
+//! A reminder goes off either a number of minutes before its task is due, at a fixed time, or
+//! when the user arrives at or leaves a place (see `ReminderKind`). The REST API doesn't cover
+//! reminders, so these functions go through the Sync API instead. Reminders can be downloaded
+//! (either all at once or for one task), created, updated and deleted:
+//!
+//! - To get all reminders, use `get_all_reminders()`
+//! - To get the reminders on one task, use `get_task_reminders()`
+//! - To add a reminder to a task, make a `NewReminder` struct and call `upload()` on it
+//! - To update a reminder whose ID you know, make an `UpdateReminder` struct and call `update()`
+//!   on it
+//! - To delete a reminder whose ID you know, run `delete_reminder_by_id()`
+//!
+//! The same changes can be made alongside others in a `sync::CommandBatch`.
+//! ```no_run
+//! use todoist_v2_rest::{TodoistUser, reminders};
+//! let user = TodoistUser::new("a2a72c2f394b265bb798d5dc4ef55be51443d519");
+//! let reminder = reminders::NewReminder {
+//!     task_id: "2995104339".to_string(),
+//!     kind: reminders::ReminderKind::Relative { minutes_before: 30 },
+//!     notify_uid: None,
+//! };
+//! reminder.upload(&user).expect("Couldn't add reminder");
+//! ```
+
+
+mod structs;
+
+pub use structs::reminder::{LocationTrigger, Reminder, ReminderKind};
+pub use structs::new_reminder::NewReminder;
+pub use structs::update_reminder::UpdateReminder;
+
+use crate::err::TodoistAPIError;
+use crate::sync::{self, CommandBatch};
+use crate::TodoistUser;
+#[cfg(feature = "async")]
+use crate::AsyncTodoistClient;
+
+
+/// Get a Vec of all the reminders on the user's tasks.
+pub fn get_all_reminders(user: &TodoistUser) -> Result<Vec<Reminder>, TodoistAPIError> {
+    // Send the API request
+    let response = user.send(sync::resource_request("reminders"));
+    sync::resources_from_response(response, "reminders")
+}
+
+/// Asynchronous version of `get_all_reminders()`.
+#[cfg(feature = "async")]
+pub async fn get_all_reminders_async(client: &AsyncTodoistClient) -> Result<Vec<Reminder>, TodoistAPIError> {
+    let response = client.send(sync::resource_request("reminders")).await;
+    sync::resources_from_response(response, "reminders")
+}
+
+/// Get a Vec of the reminders on the task with the given ID.
+pub fn get_task_reminders(user: &TodoistUser, task_id: &str) -> Result<Vec<Reminder>, TodoistAPIError> {
+    let mut reminders = get_all_reminders(user)?;
+    reminders.retain(|reminder| reminder.task_id == task_id);
+    Ok(reminders)
+}
+
+/// Asynchronous version of `get_task_reminders()`.
+#[cfg(feature = "async")]
+pub async fn get_task_reminders_async(client: &AsyncTodoistClient, task_id: &str) -> Result<Vec<Reminder>, TodoistAPIError> {
+    let mut reminders = get_all_reminders_async(client).await?;
+    reminders.retain(|reminder| reminder.task_id == task_id);
+    Ok(reminders)
+}
+
+
+/// Delete the reminder with the given ID.
+pub fn delete_reminder_by_id(user: &TodoistUser, id: &str) -> Result<(), TodoistAPIError> {
+    let mut batch = CommandBatch::new();
+    batch.delete_reminder(id);
+    batch.submit(user)?.into_result().map(|_| ())
+}
+
+/// Asynchronous version of `delete_reminder_by_id()`.
+#[cfg(feature = "async")]
+pub async fn delete_reminder_by_id_async(client: &AsyncTodoistClient, id: &str) -> Result<(), TodoistAPIError> {
+    let mut batch = CommandBatch::new();
+    batch.delete_reminder(id);
+    batch.submit_async(client).await?.into_result().map(|_| ())
+}
+
+
+/// Pick out the reminder a command just created or updated.
+fn find_reminder(reminders: Vec<Reminder>, id: &str) -> Result<Reminder, TodoistAPIError> {
+    reminders.into_iter()
+        .find(|reminder| reminder.id == id)
+        .ok_or_else(|| TodoistAPIError::ObjectNotReturned {
+            resource: "reminder".to_string(),
+            id: id.to_string(),
+        })
+}
@@ -0,0 +1,3 @@
+pub mod reminder;
+pub mod new_reminder;
+pub mod update_reminder;
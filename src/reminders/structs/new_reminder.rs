@@ -0,0 +1,46 @@
+//! Defines the structure used to add a reminder to a task.
+
+
+use crate::err::TodoistAPIError;
+use crate::reminders::{find_reminder, get_all_reminders, Reminder, ReminderKind};
+use crate::sync::CommandBatch;
+use crate::TodoistUser;
+#[cfg(feature = "async")]
+use crate::AsyncTodoistClient;
+#[cfg(feature = "async")]
+use crate::reminders::get_all_reminders_async;
+
+
+/// The structure used to add a reminder to a task. Create an instance of this struct with the
+/// chosen options, then call the `upload` method to upload it to the Todoist API.
+///
+/// If `notify_uid` is not specified, the reminder will be for the current user.
+#[derive(Debug, Clone)]
+#[allow(missing_docs)]
+pub struct NewReminder {
+    pub task_id: String,
+    pub kind: ReminderKind,
+    pub notify_uid: Option<String>,
+}
+
+
+impl NewReminder {
+    /// Add the reminder, returning it as Todoist stored it.
+    pub fn upload(&self, user: &TodoistUser) -> Result<Reminder, TodoistAPIError> {
+        let mut batch = CommandBatch::new();
+        let temp_id = batch.add_reminder(self);
+        let results = batch.submit(user)?.into_result()?;
+        let id = results.real_id(&temp_id).unwrap_or(&temp_id);
+        find_reminder(get_all_reminders(user)?, id)
+    }
+
+    /// Asynchronous version of `upload()`.
+    #[cfg(feature = "async")]
+    pub async fn upload_async(&self, client: &AsyncTodoistClient) -> Result<Reminder, TodoistAPIError> {
+        let mut batch = CommandBatch::new();
+        let temp_id = batch.add_reminder(self);
+        let results = batch.submit_async(client).await?.into_result()?;
+        let id = results.real_id(&temp_id).unwrap_or(&temp_id);
+        find_reminder(get_all_reminders_async(client).await?, id)
+    }
+}
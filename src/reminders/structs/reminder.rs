@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+
+/// Represents a reminder on a task, as returned by the API.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "SyncReminder")]
+pub struct Reminder {
+    /// The ID of the reminder.
+    pub id: String,
+
+    /// The ID of the task the reminder is for.
+    pub task_id: String,
+
+    /// The ID of the user who will be reminded.
+    pub notify_uid: String,
+
+    /// When the reminder goes off.
+    pub kind: ReminderKind,
+}
+
+/// When a reminder goes off.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReminderKind {
+    /// A number of minutes before the task is due. The task must have a due time, not just a date.
+    Relative {
+        /// How many minutes before the task's due time to remind the user.
+        minutes_before: u32,
+    },
+
+    /// At a fixed time.
+    Absolute {
+        /// The time to remind the user, in RFC3339 format in UTC, e.g. `2024-01-31T09:00:00Z`.
+        datetime: String,
+    },
+
+    /// When the user arrives at or leaves a place.
+    Location {
+        /// The name of the place.
+        name: String,
+        /// The latitude of the place.
+        latitude: f64,
+        /// The longitude of the place.
+        longitude: f64,
+        /// Whether to remind the user on arriving or leaving.
+        trigger: LocationTrigger,
+        /// How close to the place, in metres, counts as being there. Todoist chooses if this is
+        /// `None`.
+        radius: Option<u32>,
+    },
+}
+
+/// Whether a location reminder goes off on arriving at the place or leaving it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(missing_docs)]
+#[serde(rename_all = "snake_case")]
+pub enum LocationTrigger {
+    OnEnter,
+    OnLeave,
+}
+
+
+/// A reminder as the Sync API returns it, with the fields for every kind of reminder.
+#[derive(Deserialize)]
+struct SyncReminder {
+    id: String,
+    item_id: String,
+    notify_uid: String,
+    #[serde(rename = "type")]
+    kind: String,
+    minute_offset: Option<u32>,
+    due: Option<SyncReminderDue>,
+    name: Option<String>,
+    loc_lat: Option<String>,
+    loc_long: Option<String>,
+    loc_trigger: Option<LocationTrigger>,
+    radius: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct SyncReminderDue {
+    date: String,
+}
+
+impl TryFrom<SyncReminder> for Reminder {
+    type Error = String;
+
+    fn try_from(reminder: SyncReminder) -> Result<Reminder, String> {
+        let missing = |field: &str| format!("{} reminder {} has no {}", reminder.kind, reminder.id, field);
+        let kind = match reminder.kind.as_str() {
+            "relative" => ReminderKind::Relative {
+                minutes_before: reminder.minute_offset.ok_or_else(|| missing("minute_offset"))?,
+            },
+            "absolute" => ReminderKind::Absolute {
+                datetime: reminder.due.as_ref().ok_or_else(|| missing("due"))?.date.clone(),
+            },
+            "location" => {
+                let coordinate = |value: &Option<String>, field: &str| value.as_deref()
+                    .ok_or_else(|| missing(field))?
+                    .parse::<f64>()
+                    .map_err(|err| format!("Reminder {} has an invalid {}: {}", reminder.id, field, err));
+                ReminderKind::Location {
+                    name: reminder.name.clone().ok_or_else(|| missing("name"))?,
+                    latitude: coordinate(&reminder.loc_lat, "loc_lat")?,
+                    longitude: coordinate(&reminder.loc_long, "loc_long")?,
+                    trigger: reminder.loc_trigger.ok_or_else(|| missing("loc_trigger"))?,
+                    radius: reminder.radius,
+                }
+            }
+            other => return Err(format!("Reminder {} has unknown type {:?}", reminder.id, other)),
+        };
+        Ok(Reminder {
+            id: reminder.id,
+            task_id: reminder.item_id,
+            notify_uid: reminder.notify_uid,
+            kind,
+        })
+    }
+}
@@ -0,0 +1,43 @@
+//! Defines the structure used to update an existing reminder
+
+
+use crate::err::TodoistAPIError;
+use crate::reminders::{find_reminder, get_all_reminders, Reminder, ReminderKind};
+use crate::sync::CommandBatch;
+use crate::TodoistUser;
+#[cfg(feature = "async")]
+use crate::AsyncTodoistClient;
+#[cfg(feature = "async")]
+use crate::reminders::get_all_reminders_async;
+
+/// The structure used to update an existing reminder. The kind of reminder (including when it
+/// goes off) and the user it is for can both be updated. Any field left None will not be updated.
+///
+/// Once selecting the appropriate options, call the `update` method with the ID of the reminder to
+/// apply the changes.
+#[derive(Debug, Clone, Default)]
+#[allow(missing_docs)]
+pub struct UpdateReminder {
+    pub kind: Option<ReminderKind>,
+    pub notify_uid: Option<String>,
+}
+
+
+impl UpdateReminder {
+    /// Update the reminder with the given ID.
+    pub fn update(&self, user: &TodoistUser, id: &str) -> Result<Reminder, TodoistAPIError> {
+        let mut batch = CommandBatch::new();
+        batch.update_reminder(id, self);
+        batch.submit(user)?.into_result()?;
+        find_reminder(get_all_reminders(user)?, id)
+    }
+
+    /// Asynchronous version of `update()`.
+    #[cfg(feature = "async")]
+    pub async fn update_async(&self, client: &AsyncTodoistClient, id: &str) -> Result<Reminder, TodoistAPIError> {
+        let mut batch = CommandBatch::new();
+        batch.update_reminder(id, self);
+        batch.submit_async(client).await?.into_result()?;
+        find_reminder(get_all_reminders_async(client).await?, id)
+    }
+}
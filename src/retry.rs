@@ -0,0 +1,179 @@
+//! Controls how failed requests are retried.
+//!
+//! Every `TodoistClient` has a `RetryPolicy`, which is `RetryPolicy::default()` unless another is
+//! given to the builder. A request is retried if:
+//! - it couldn't be sent, or no response was received (e.g. a connection error or timeout), or
+//! - Todoist responded with `429 Too Many Requests`, or
+//! - Todoist responded with a `5xx` server error.
+//!
+//! Between attempts the client waits for an exponentially growing backoff, with some random jitter
+//! so that many clients don't all retry at the same moment. If the response had a `Retry-After`
+//! header, the client waits for that long instead, whether it gives a number of seconds or a date
+//! (in the `Wed, 21 Oct 2015 07:28:00 GMT` form servers are required to send).
+//!
+//! Only requests which are safe to repeat are retried. GET and DELETE requests always are, but a
+//! POST request (e.g. creating a task) is only retried if it carries an `X-Request-Id` header, which
+//! Todoist uses to ignore the duplicate if the first attempt did in fact succeed. Every POST made
+//! by this crate carries one, and it stays the same across retries (see the `client` module).
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use todoist_v2_rest::TodoistClient;
+//! use todoist_v2_rest::retry::RetryPolicy;
+//! let client = TodoistClient::builder("a2a72c2f394b265bb798d5dc4ef55be51443d519")
+//!     .retry_policy(RetryPolicy {
+//!         max_attempts: 5,
+//!         initial_backoff: Duration::from_secs(1),
+//!         ..RetryPolicy::default()
+//!     })
+//!     .build()
+//!     .expect("Couldn't build client");
+//! ```
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+
+
+/// Describes how many times, and how quickly, failed requests are retried.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// The maximum number of times a request is sent, including the first attempt. A value of 1
+    /// (or 0) disables retrying.
+    pub max_attempts: u32,
+
+    /// How long to wait before the first retry.
+    pub initial_backoff: Duration,
+
+    /// The longest the backoff is allowed to grow to. This doesn't limit waits requested by a
+    /// `Retry-After` header.
+    pub max_backoff: Duration,
+
+    /// The factor the backoff is multiplied by after each retry.
+    pub multiplier: f64,
+
+    /// The proportion of each backoff which is randomised, from 0.0 (always wait exactly the
+    /// backoff) to 1.0 (wait anywhere between zero and the backoff).
+    pub jitter: f64,
+}
+
+impl RetryPolicy {
+    /// A policy which never retries, so every request is sent exactly once.
+    pub fn none() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// The backoff to wait after the given attempt (counting from 1) has failed, before any
+    /// jitter is applied.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let backoff = self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent);
+        Duration::from_secs_f64(backoff.min(self.max_backoff.as_secs_f64()).max(0.0))
+    }
+
+    /// The backoff to wait after the given attempt has failed, with jitter applied.
+    fn jittered_backoff(&self, attempt: u32) -> Duration {
+        let backoff = self.backoff(attempt);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        backoff.mul_f64(1.0 - jitter * fastrand::f64())
+    }
+
+    /// Decide whether the attempt (counting from 1) should be retried, and if so how long to wait
+    /// first. Returns `None` if the outcome should be returned to the caller as it is.
+    pub(crate) fn retry_delay(&self, attempt: u32, repeatable: bool, outcome: Attempt) -> Option<Duration> {
+        if attempt >= self.max_attempts || !repeatable {
+            return None;
+        }
+
+        match outcome {
+            Attempt::Failed => Some(self.jittered_backoff(attempt)),
+            Attempt::Responded(status, headers) => {
+                if status != StatusCode::TOO_MANY_REQUESTS && !status.is_server_error() {
+                    return None;
+                }
+                Some(retry_after(headers).unwrap_or_else(|| self.jittered_backoff(attempt)))
+            }
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Up to 3 attempts, waiting around 0.5s and then 1s between them.
+    fn default() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: 0.2,
+        }
+    }
+}
+
+
+/// The outcome of a single attempt at sending a request.
+pub(crate) enum Attempt<'a> {
+    /// The request couldn't be sent, or no response was received.
+    Failed,
+
+    /// A response was received, with this status and headers.
+    Responded(StatusCode, &'a HeaderMap),
+}
+
+
+/// Read the `Retry-After` header, if there is one giving either a number of seconds or the date
+/// to retry after. A date in the past means there is no need to wait.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = http_date(value)?;
+    Some(date.duration_since(SystemTime::now()).unwrap_or(Duration::ZERO))
+}
+
+/// Parse an HTTP date in the preferred IMF-fixdate form, e.g. `Wed, 21 Oct 2015 07:28:00 GMT`. The
+/// obsolete RFC 850 and asctime forms aren't supported, and nor are dates which don't exist or are
+/// outside the years 1970 to 9999.
+fn http_date(value: &str) -> Option<SystemTime> {
+    const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+    let parts: Vec<&str> = value.split_whitespace().collect();
+    let [weekday, day, month, year, time, "GMT"] = parts[..] else {
+        return None;
+    };
+    if !weekday.ends_with(',') {
+        return None;
+    }
+    let month = MONTHS.iter().position(|name| *name == month)? as u64 + 1;
+    let year: u64 = year.parse().ok().filter(|year| (1970..=9999).contains(year))?;
+    let is_leap_year = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+    let days_in_month = match month {
+        2 if is_leap_year => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    let day: u64 = day.parse().ok().filter(|day| (1..=days_in_month).contains(day))?;
+    let time: Vec<u64> = time.split(':').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    let [hours, minutes, seconds] = time[..] else {
+        return None;
+    };
+    if hours > 23 || minutes > 59 || seconds > 60 {
+        return None;
+    }
+
+    // Count the days since 1970-01-01, treating the year as starting in March so that leap days
+    // fall at its end
+    let (year, month) = if month <= 2 { (year - 1, month + 9) } else { (year, month - 3) };
+    let day_of_year = (153 * month + 2) / 5 + day - 1;
+    let days = year.checked_mul(365)?
+        .checked_add(year / 4 - year / 100 + year / 400 + day_of_year)?
+        .checked_sub(719_468)?;
+    let seconds = days.checked_mul(86_400)?
+        .checked_add(hours * 3_600 + minutes * 60 + seconds)?;
+    UNIX_EPOCH.checked_add(Duration::from_secs(seconds))
+}
@@ -22,18 +22,23 @@ pub use structs::section::Section;
 pub use structs::new_section::NewSection;
 
 use crate::err::TodoistAPIError;
-use crate::general::{get_204_from_reqwest_response, get_from_reqwest_response};
+use crate::general::{get_204_from_response, get_from_response, ApiRequest};
 use crate::TodoistUser;
+#[cfg(feature = "async")]
+use crate::AsyncTodoistClient;
 
 /// Return a `Vec` of all sections in every project
 pub fn get_all_sections(user: &TodoistUser) -> Result<Vec<Section>, TodoistAPIError> {
     // Send the API request
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .get("https://api.todoist.com/rest/v2/sections")
-        .header("Authorization", String::from("Bearer ") + &user.token)
-        .send();
-    get_from_reqwest_response(response)
+    let response = user.send(ApiRequest::get("/sections"));
+    get_from_response(response)
+}
+
+/// Asynchronous version of `get_all_sections()`.
+#[cfg(feature = "async")]
+pub async fn get_all_sections_async(client: &AsyncTodoistClient) -> Result<Vec<Section>, TodoistAPIError> {
+    let response = client.send(ApiRequest::get("/sections")).await;
+    get_from_response(response)
 }
 
 /// Return a `Vec` of all sections in the given project
@@ -42,52 +47,77 @@ pub fn get_all_sections_in_project(
     project_id: &str,
 ) -> Result<Vec<Section>, TodoistAPIError> {
     // Send the API request
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .get(format!(
-            "https://api.todoist.com/rest/v2/sections?project_id={}",
-            project_id
-        ))
-        .header("Authorization", String::from("Bearer ") + &user.token)
-        .send();
-    get_from_reqwest_response(response)
+    let response = user.send(ApiRequest::get("/sections").query("project_id", project_id));
+    get_from_response(response)
+}
+
+/// Asynchronous version of `get_all_sections_in_project()`.
+#[cfg(feature = "async")]
+pub async fn get_all_sections_in_project_async(
+    client: &AsyncTodoistClient,
+    project_id: &str,
+) -> Result<Vec<Section>, TodoistAPIError> {
+    let response = client.send(ApiRequest::get("/sections").query("project_id", project_id)).await;
+    get_from_response(response)
 }
 
 
 /// Get an individual Section by its ID.
 pub fn get_section_by_id(user: &TodoistUser, id: &str) -> Result<Section, TodoistAPIError> {
     // Send the API request
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .get(format!("https://api.todoist.com/rest/v2/sections/{}", id))
-        .header("Authorization", String::from("Bearer ") + &user.token)
-        .send();
-    get_from_reqwest_response(response)
+    let response = user.send(ApiRequest::get(&format!("/sections/{}", id)).resource("section", id));
+    get_from_response(response)
+}
+
+/// Asynchronous version of `get_section_by_id()`.
+#[cfg(feature = "async")]
+pub async fn get_section_by_id_async(client: &AsyncTodoistClient, id: &str) -> Result<Section, TodoistAPIError> {
+    let request = ApiRequest::get(&format!("/sections/{}", id))
+        .resource("section", id);
+    let response = client.send(request).await;
+    get_from_response(response)
 }
 
 
 /// Delete the section with the given ID.
 pub fn delete_section_by_id(user: &TodoistUser, id: &str) -> Result<(), TodoistAPIError> {
     // Send the API request
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .delete(format!("https://api.todoist.com/rest/v2/sections/{}", id))
-        .header("Authorization", String::from("Bearer ") + &user.token)
-        .send();
-    get_204_from_reqwest_response(response)
+    let request = ApiRequest::delete(&format!("/sections/{}", id))
+        .resource("section", id);
+    let response = user.send(request);
+    get_204_from_response(response)
+}
+
+/// Asynchronous version of `delete_section_by_id()`.
+#[cfg(feature = "async")]
+pub async fn delete_section_by_id_async(client: &AsyncTodoistClient, id: &str) -> Result<(), TodoistAPIError> {
+    let request = ApiRequest::delete(&format!("/sections/{}", id))
+        .resource("section", id);
+    let response = client.send(request).await;
+    get_204_from_response(response)
 }
 
 
 /// Change the section's name
 pub fn update_section_name(user: &TodoistUser, id: &str, new_name: &str) -> Result<Section, TodoistAPIError> {
     // Send the API request
-    let client = reqwest::blocking::Client::new();
     let mut map = std::collections::HashMap::new();
     map.insert("name", new_name);
-    let response = client
-        .post(format!("https://api.todoist.com/rest/v2/sections/{}", id))
-        .header("Authorization", String::from("Bearer ") + &user.token)
-        .json(&map)
-        .send();
-    get_from_reqwest_response(response)
+    let request = ApiRequest::post(&format!("/sections/{}", id))
+        .resource("section", id)
+        .json(&map)?;
+    let response = user.send(request);
+    get_from_response(response)
+}
+
+/// Asynchronous version of `update_section_name()`.
+#[cfg(feature = "async")]
+pub async fn update_section_name_async(client: &AsyncTodoistClient, id: &str, new_name: &str) -> Result<Section, TodoistAPIError> {
+    let mut map = std::collections::HashMap::new();
+    map.insert("name", new_name);
+    let request = ApiRequest::post(&format!("/sections/{}", id))
+        .resource("section", id)
+        .json(&map)?;
+    let response = client.send(request).await;
+    get_from_response(response)
 }
@@ -1,8 +1,10 @@
 use serde::Serialize;
 use crate::err::TodoistAPIError;
-use crate::general::get_from_reqwest_response;
+use crate::general::{get_from_response, ApiRequest};
 use crate::sections::Section;
 use crate::TodoistUser;
+#[cfg(feature = "async")]
+use crate::AsyncTodoistClient;
 
 /// The structure to represent a new section to be created.
 ///
@@ -24,12 +26,14 @@ impl NewSection {
     /// Upload the new section to the Todoist API.
     pub fn upload(&self, user: &TodoistUser) -> Result<Section, TodoistAPIError> {
         // Send the API request
-        let client = reqwest::blocking::Client::new();
-        let response = client
-            .post("https://api.todoist.com/rest/v2/sections")
-            .header("Authorization", String::from("Bearer ") + &user.token)
-            .json(self)
-            .send();
-        get_from_reqwest_response(response)
+        let response = user.send(ApiRequest::post("/sections").json(self)?);
+        get_from_response(response)
+    }
+
+    /// Asynchronous version of `upload()`.
+    #[cfg(feature = "async")]
+    pub async fn upload_async(&self, client: &AsyncTodoistClient) -> Result<Section, TodoistAPIError> {
+        let response = client.send(ApiRequest::post("/sections").json(self)?).await;
+        get_from_response(response)
     }
 }
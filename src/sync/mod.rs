@@ -0,0 +1,137 @@
+//! Getting all of the user's objects in a single request through Todoist's Sync API, and then
+//! getting only what has changed since.
+//!
+//! <https://developer.todoist.com/sync/v9>
+//!
+//! Rather than fetching tasks, projects, sections, labels and comments separately, a full sync
+//! returns all of them at once, along with a sync token. Giving that token to an incremental sync
+//! returns only the objects which have been added or changed since, the IDs of those which have
+//! been deleted, and a new token for the next sync.
+//!
+//! - To get all of the user's objects, use `full_sync()`
+//! - To get what has changed since an earlier sync, use `incremental_sync()` with its sync token
+//!
+//! The objects are returned as the same `Task`, `Project`, `Section`, `Label` and `Comment` types
+//! the rest of the crate uses.
+//! ```no_run
+//! use todoist_v2_rest::{TodoistUser, sync};
+//! let user = TodoistUser::new("a2a72c2f394b265bb798d5dc4ef55be51443d519");
+//! let everything = sync::full_sync(&user).expect("Couldn't sync");
+//! println!("{} tasks", everything.tasks.len());
+//!
+//! // Later on
+//! let changes = sync::incremental_sync(&user, &everything.sync_token).expect("Couldn't sync");
+//! println!("{} tasks changed, {} deleted", changes.tasks.len(), changes.deleted.tasks.len());
+//! ```
+//!
+//! The Sync API can also make many changes in a single request. Add commands to a `CommandBatch`,
+//! then send them all with `CommandBatch::submit()`. Objects created by the batch get temporary
+//! IDs, which later commands can use in place of real IDs:
+//! ```no_run
+//! use todoist_v2_rest::{TodoistUser, projects, sync, tasks};
+//! let user = TodoistUser::new("a2a72c2f394b265bb798d5dc4ef55be51443d519");
+//! let mut batch = sync::CommandBatch::new();
+//! let project_id = batch.add_project(&projects::NewProject {
+//!     name: "Moving house".to_string(),
+//!     parent_id: None,
+//!     color: None,
+//!     is_favorite: None,
+//!     view_style: None,
+//! });
+//! for content in ["Book van", "Pack boxes", "Redirect post"] {
+//!     let mut task = tasks::NewTask::new(content);
+//!     task.project_id = Some(project_id.clone());
+//!     batch.add_task(&task);
+//! }
+//! let results = batch.submit(&user).expect("Couldn't send commands");
+//! assert!(results.is_ok());
+//! println!("Created project {:?}", results.real_id(&project_id));
+//! ```
+
+
+mod structs;
+
+pub use structs::sync_changes::{DeletedIds, SyncChanges};
+pub use structs::command_batch::{CommandBatch, COMMAND_LIMIT};
+pub use structs::batch_results::{BatchResults, CommandError, CommandResult};
+
+use serde::de::DeserializeOwned;
+use serde_json::{json, Map, Value};
+use crate::err::TodoistAPIError;
+use crate::general::{get_from_response, ApiRequest};
+use crate::transport::HttpResponse;
+use crate::TodoistUser;
+#[cfg(feature = "async")]
+use crate::AsyncTodoistClient;
+use structs::resources::SyncResponse;
+pub(crate) use structs::resources::{SyncItem, SyncProject};
+
+/// The sync token which asks for a full sync.
+pub const FULL_SYNC_TOKEN: &str = "*";
+
+/// The resource types every sync asks for.
+const RESOURCE_TYPES: &str = r#"["items","projects","sections","labels","notes","project_notes"]"#;
+
+
+/// Get all of the user's tasks, projects, sections, labels and comments, along with a sync token
+/// for later incremental syncs.
+pub fn full_sync(user: &TodoistUser) -> Result<SyncChanges, TodoistAPIError> {
+    incremental_sync(user, FULL_SYNC_TOKEN)
+}
+
+/// Asynchronous version of `full_sync()`.
+#[cfg(feature = "async")]
+pub async fn full_sync_async(client: &AsyncTodoistClient) -> Result<SyncChanges, TodoistAPIError> {
+    incremental_sync_async(client, FULL_SYNC_TOKEN).await
+}
+
+/// Get everything which has been added, changed or deleted since the sync which returned the
+/// given sync token.
+pub fn incremental_sync(user: &TodoistUser, sync_token: &str) -> Result<SyncChanges, TodoistAPIError> {
+    // Send the API request
+    let response = user.send(sync_request(sync_token));
+    changes_from_response(response)
+}
+
+/// Asynchronous version of `incremental_sync()`.
+#[cfg(feature = "async")]
+pub async fn incremental_sync_async(client: &AsyncTodoistClient, sync_token: &str) -> Result<SyncChanges, TodoistAPIError> {
+    let response = client.send(sync_request(sync_token)).await;
+    changes_from_response(response)
+}
+
+
+/// Build the read request for a sync with the given token.
+fn sync_request(sync_token: &str) -> ApiRequest {
+    ApiRequest::sync_read("/sync")
+        .form(&[("sync_token", sync_token), ("resource_types", RESOURCE_TYPES)])
+}
+
+/// Build the request for a full sync of only one resource type, for the objects (such as filters)
+/// which only the Sync API covers.
+pub(crate) fn resource_request(resource_type: &str) -> ApiRequest {
+    let resource_types = json!([resource_type]).to_string();
+    ApiRequest::sync_read("/sync")
+        .form(&[("sync_token", FULL_SYNC_TOKEN), ("resource_types", &resource_types)])
+}
+
+/// Deserialise the objects of one resource type from the response to a `resource_request()`,
+/// leaving out any which have been deleted (which Todoist may send without their other fields).
+pub(crate) fn resources_from_response<T: DeserializeOwned>(response: Result<HttpResponse, TodoistAPIError>, resource_type: &str) -> Result<Vec<T>, TodoistAPIError> {
+    let mut response: Map<String, Value> = get_from_response(response)?;
+    let objects = match response.remove(resource_type) {
+        Some(Value::Array(objects)) => objects,
+        _ => Vec::new(),
+    };
+    objects.into_iter()
+        .filter(|object| object["is_deleted"].as_bool() != Some(true))
+        .map(serde_json::from_value)
+        .collect::<Result<_, _>>()
+        .map_err(TodoistAPIError::DeserialisationError)
+}
+
+/// Deserialise the response to a sync, and convert its objects to the crate's types.
+fn changes_from_response(response: Result<HttpResponse, TodoistAPIError>) -> Result<SyncChanges, TodoistAPIError> {
+    let response: SyncResponse = get_from_response(response)?;
+    response.into_changes().map_err(TodoistAPIError::DeserialisationError)
+}
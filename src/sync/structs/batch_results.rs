@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::err::TodoistAPIError;
+
+/// The results of submitting a `CommandBatch`.
+#[derive(Debug, Default)]
+pub struct BatchResults {
+    /// The result of each command, in the order they were added to the batch.
+    pub results: Vec<CommandResult>,
+
+    /// The real ID of each object created by the batch, by its temporary ID.
+    pub temp_id_mapping: HashMap<String, String>,
+}
+
+impl BatchResults {
+    /// Whether every command succeeded.
+    pub fn is_ok(&self) -> bool {
+        self.results.iter().all(|result| result.error.is_none())
+    }
+
+    /// Turn the first command which failed (if any) into a `CommandError`, for when every
+    /// command must succeed.
+    pub fn into_result(mut self) -> Result<BatchResults, TodoistAPIError> {
+        match self.results.iter_mut().find_map(|result| result.error.take()) {
+            Some(error) => Err(TodoistAPIError::CommandError(error)),
+            None => Ok(self),
+        }
+    }
+
+    /// The real ID of the object created with the given temporary ID, if it was created.
+    pub fn real_id(&self, temp_id: &str) -> Option<&str> {
+        self.temp_id_mapping.get(temp_id).map(String::as_str)
+    }
+}
+
+
+/// The result of a single command.
+#[derive(Debug)]
+pub struct CommandResult {
+    /// The UUID the command was sent with.
+    pub uuid: String,
+
+    /// The temporary ID of the object the command created, if it was an `add_` command.
+    pub temp_id: Option<String>,
+
+    /// Why the command failed, or `None` if it succeeded.
+    pub error: Option<CommandError>,
+}
+
+
+/// An error returned by Todoist for a single command.
+#[derive(Debug, Deserialize)]
+pub struct CommandError {
+    /// Todoist's code for the error.
+    pub error_code: i64,
+
+    /// A description of the error.
+    pub error: String,
+}
@@ -0,0 +1,459 @@
+use std::collections::HashMap;
+
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use crate::err::TodoistAPIError;
+use crate::filters::{NewFilter, UpdateFilter};
+use crate::general::{get_from_response, ApiRequest};
+use crate::labels::NewLabel;
+use crate::projects::NewProject;
+use crate::reminders::{NewReminder, ReminderKind, UpdateReminder};
+use crate::sections::NewSection;
+use crate::sync::{BatchResults, CommandError, CommandResult};
+use crate::tasks::{DurationUnit, NewDue, NewDuration, NewTask, TaskDestination, UpdateTask};
+use crate::TodoistUser;
+#[cfg(feature = "async")]
+use crate::AsyncTodoistClient;
+
+/// The most commands Todoist accepts in a single request. Larger batches are split into requests
+/// of this many commands.
+pub const COMMAND_LIMIT: usize = 100;
+
+
+/// A list of write commands, to be sent to Todoist together with `submit()`.
+///
+/// Each `add_` method returns a temporary ID for the object it will create. This can be used in
+/// place of a real ID in any later command in the same batch (e.g. as the `project_id` of a
+/// `NewTask`), and the real IDs are given in the `BatchResults`.
+#[derive(Debug, Default)]
+pub struct CommandBatch {
+    commands: Vec<Command>,
+}
+
+/// A single command, as sent to the Sync API.
+#[derive(Debug, Clone)]
+struct Command {
+    kind: &'static str,
+    uuid: String,
+    temp_id: Option<String>,
+    args: Value,
+}
+
+impl CommandBatch {
+    /// Create an empty batch.
+    pub fn new() -> CommandBatch {
+        CommandBatch::default()
+    }
+
+    /// The number of commands in the batch.
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Whether the batch has no commands.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Add a task, returning its temporary ID.
+    pub fn add_task(&mut self, task: &NewTask) -> String {
+        let mut args = Map::new();
+        args.insert("content".to_string(), json!(task.content));
+        insert(&mut args, "description", &task.description);
+        insert(&mut args, "project_id", &task.project_id);
+        insert(&mut args, "section_id", &task.section_id);
+        insert(&mut args, "parent_id", &task.parent_id);
+        insert(&mut args, "child_order", &task.order);
+        insert(&mut args, "labels", &task.labels);
+        insert(&mut args, "priority", &task.priority);
+        insert(&mut args, "due", &task.due.as_ref().map(due_args));
+        insert(&mut args, "responsible_uid", &task.assignee_id);
+        insert(&mut args, "duration", &task.duration.as_ref().map(duration_args));
+        self.push_add("item_add", args)
+    }
+
+    /// Update the task with the given ID.
+    pub fn update_task(&mut self, task_id: &str, task: &UpdateTask) {
+        let mut args = Map::new();
+        args.insert("id".to_string(), json!(task_id));
+        insert(&mut args, "content", &task.content);
+        insert(&mut args, "description", &task.description);
+        insert(&mut args, "labels", &task.labels);
+        insert(&mut args, "priority", &task.priority);
+        insert(&mut args, "due", &task.due.as_ref().map(due_args));
+        insert(&mut args, "responsible_uid", &task.assignee_id);
+        insert(&mut args, "duration", &task.duration.as_ref().map(duration_args));
+        self.push("item_update", None, Value::Object(args));
+    }
+
+    /// Close (complete) the task with the given ID.
+    pub fn close_task(&mut self, task_id: &str) {
+        self.push("item_close", None, json!({"id": task_id}));
+    }
+
+    /// Reopen (uncomplete) the task with the given ID.
+    pub fn reopen_task(&mut self, task_id: &str) {
+        self.push("item_uncomplete", None, json!({"id": task_id}));
+    }
+
+    /// Delete the task with the given ID, along with its subtasks.
+    pub fn delete_task(&mut self, task_id: &str) {
+        self.push("item_delete", None, json!({"id": task_id}));
+    }
+
+    /// Move the task with the given ID, along with its subtasks, to another project, section or
+    /// parent task.
+    pub fn move_task(&mut self, task_id: &str, destination: &TaskDestination) {
+        let mut args = json!({"id": task_id});
+        match destination {
+            TaskDestination::Project(project_id) => args["project_id"] = json!(project_id),
+            TaskDestination::Section(section_id) => args["section_id"] = json!(section_id),
+            TaskDestination::Parent(parent_id) => args["parent_id"] = json!(parent_id),
+        }
+        self.push("item_move", None, args);
+    }
+
+    /// Add a project, returning its temporary ID.
+    pub fn add_project(&mut self, project: &NewProject) -> String {
+        let mut args = Map::new();
+        args.insert("name".to_string(), json!(project.name));
+        insert(&mut args, "parent_id", &project.parent_id);
+        insert(&mut args, "color", &project.color);
+        insert(&mut args, "is_favorite", &project.is_favorite);
+        insert(&mut args, "view_style", &project.view_style);
+        self.push_add("project_add", args)
+    }
+
+    /// Archive the project with the given ID, along with its descendants.
+    pub fn archive_project(&mut self, project_id: &str) {
+        self.push("project_archive", None, json!({"id": project_id}));
+    }
+
+    /// Unarchive the project with the given ID.
+    pub fn unarchive_project(&mut self, project_id: &str) {
+        self.push("project_unarchive", None, json!({"id": project_id}));
+    }
+
+    /// Move the project with the given ID under another project, or to the top level if
+    /// `parent_id` is `None`.
+    pub fn move_project(&mut self, project_id: &str, parent_id: Option<&str>) {
+        self.push("project_move", None, json!({"id": project_id, "parent_id": parent_id}));
+    }
+
+    /// Delete the project with the given ID, along with everything in it.
+    pub fn delete_project(&mut self, project_id: &str) {
+        self.push("project_delete", None, json!({"id": project_id}));
+    }
+
+    /// Add a section, returning its temporary ID.
+    pub fn add_section(&mut self, section: &NewSection) -> String {
+        let mut args = Map::new();
+        args.insert("name".to_string(), json!(section.name));
+        args.insert("project_id".to_string(), json!(section.project_id));
+        insert(&mut args, "section_order", &section.order);
+        self.push_add("section_add", args)
+    }
+
+    /// Delete the section with the given ID, along with its tasks.
+    pub fn delete_section(&mut self, section_id: &str) {
+        self.push("section_delete", None, json!({"id": section_id}));
+    }
+
+    /// Add a personal label, returning its temporary ID.
+    pub fn add_label(&mut self, label: &NewLabel) -> String {
+        let mut args = Map::new();
+        args.insert("name".to_string(), json!(label.name));
+        insert(&mut args, "item_order", &label.order);
+        insert(&mut args, "color", &label.color);
+        insert(&mut args, "is_favorite", &label.is_favorite);
+        self.push_add("label_add", args)
+    }
+
+    /// Delete the personal label with the given ID.
+    pub fn delete_label(&mut self, label_id: &str) {
+        self.push("label_delete", None, json!({"id": label_id}));
+    }
+
+    /// Add a saved filter, returning its temporary ID.
+    pub fn add_filter(&mut self, filter: &NewFilter) -> String {
+        let mut args = Map::new();
+        args.insert("name".to_string(), json!(filter.name));
+        args.insert("query".to_string(), json!(filter.query));
+        insert(&mut args, "item_order", &filter.order);
+        insert(&mut args, "color", &filter.color);
+        insert(&mut args, "is_favorite", &filter.is_favorite);
+        self.push_add("filter_add", args)
+    }
+
+    /// Update the saved filter with the given ID.
+    pub fn update_filter(&mut self, filter_id: &str, filter: &UpdateFilter) {
+        let mut args = Map::new();
+        args.insert("id".to_string(), json!(filter_id));
+        insert(&mut args, "name", &filter.name);
+        insert(&mut args, "query", &filter.query);
+        insert(&mut args, "item_order", &filter.order);
+        insert(&mut args, "color", &filter.color);
+        insert(&mut args, "is_favorite", &filter.is_favorite);
+        self.push("filter_update", None, Value::Object(args));
+    }
+
+    /// Give the saved filters with the given IDs the positions 1, 2, 3 and so on, in that order.
+    /// Other filters aren't moved, so the IDs should be those of every filter; otherwise use
+    /// `filters::reorder_filters()`, which fills in the rest.
+    pub fn reorder_filters(&mut self, filter_ids: &[&str]) {
+        let id_order_mapping = filter_ids.iter()
+            .enumerate()
+            .map(|(order, id)| (id.to_string(), json!(order + 1)))
+            .collect::<Map<_, _>>();
+        self.push("filter_update_orders", None, json!({"id_order_mapping": id_order_mapping}));
+    }
+
+    /// Delete the saved filter with the given ID.
+    pub fn delete_filter(&mut self, filter_id: &str) {
+        self.push("filter_delete", None, json!({"id": filter_id}));
+    }
+
+    /// Add a reminder to a task, returning the reminder's temporary ID.
+    pub fn add_reminder(&mut self, reminder: &NewReminder) -> String {
+        let mut args = reminder_args(&reminder.kind);
+        args.insert("item_id".to_string(), json!(reminder.task_id));
+        insert(&mut args, "notify_uid", &reminder.notify_uid);
+        self.push_add("reminder_add", args)
+    }
+
+    /// Update the reminder with the given ID.
+    pub fn update_reminder(&mut self, reminder_id: &str, reminder: &UpdateReminder) {
+        let mut args = reminder.kind.as_ref().map(reminder_args).unwrap_or_default();
+        args.insert("id".to_string(), json!(reminder_id));
+        insert(&mut args, "notify_uid", &reminder.notify_uid);
+        self.push("reminder_update", None, Value::Object(args));
+    }
+
+    /// Delete the reminder with the given ID.
+    pub fn delete_reminder(&mut self, reminder_id: &str) {
+        self.push("reminder_delete", None, json!({"id": reminder_id}));
+    }
+
+    /// Send every command to Todoist, in requests of up to `COMMAND_LIMIT` commands, and return
+    /// the result of each.
+    ///
+    /// Temporary IDs of objects created by earlier requests are replaced by their real IDs in later
+    /// ones. If a request fails, the commands in earlier requests will already have been applied,
+    /// but submitting the same batch again is safe: Todoist ignores commands it has already seen.
+    ///
+    /// If the client has a pinned request ID (see `TodoistClient::with_request_id()`), the
+    /// commands are sent with UUIDs and temporary IDs derived from it, so that Todoist's reply to
+    /// a repeated call still refers to them.
+    pub fn submit(&self, user: &TodoistUser) -> Result<BatchResults, TodoistAPIError> {
+        let commands = self.commands_to_send(user.pinned_request_id());
+        let mut results = BatchResults::default();
+        for (index, chunk) in commands.chunks(COMMAND_LIMIT).enumerate() {
+            // Send the API request
+            let response = user.send(commands_request(index, chunk, &results.temp_id_mapping));
+            results.extend(chunk, get_from_response(response)?);
+        }
+        results.restore_temp_ids(&self.commands, &commands);
+        Ok(results)
+    }
+
+    /// Asynchronous version of `submit()`.
+    #[cfg(feature = "async")]
+    pub async fn submit_async(&self, client: &AsyncTodoistClient) -> Result<BatchResults, TodoistAPIError> {
+        let commands = self.commands_to_send(client.pinned_request_id());
+        let mut results = BatchResults::default();
+        for (index, chunk) in commands.chunks(COMMAND_LIMIT).enumerate() {
+            let response = client.send(commands_request(index, chunk, &results.temp_id_mapping)).await;
+            results.extend(chunk, get_from_response(response)?);
+        }
+        results.restore_temp_ids(&self.commands, &commands);
+        Ok(results)
+    }
+
+    /// The commands as they should be sent. With a pinned request ID, each command's UUID and
+    /// temporary ID are replaced by ones derived from the pinned ID and the command's position,
+    /// and any use of a temporary ID in a later command is replaced to match.
+    fn commands_to_send(&self, pinned_request_id: Option<&str>) -> Vec<Command> {
+        let Some(pinned_request_id) = pinned_request_id else {
+            return self.commands.clone();
+        };
+        let derived_id = |kind: &str, index: usize| {
+            let name = format!("{}/{}/{}", pinned_request_id, kind, index);
+            uuid::Uuid::new_v5(&uuid::Uuid::NAMESPACE_OID, name.as_bytes()).to_string()
+        };
+        let mut temp_ids = HashMap::new();
+        self.commands.iter()
+            .enumerate()
+            .map(|(index, command)| {
+                let mut args = command.args.clone();
+                replace_temp_ids(&mut args, &temp_ids);
+                let temp_id = command.temp_id.as_ref().map(|temp_id| {
+                    let derived = derived_id("temp_id", index);
+                    temp_ids.insert(temp_id.clone(), derived.clone());
+                    derived
+                });
+                Command { kind: command.kind, uuid: derived_id("uuid", index), temp_id, args }
+            })
+            .collect()
+    }
+
+    /// Add a command which creates an object, returning the object's temporary ID.
+    fn push_add(&mut self, kind: &'static str, args: Map<String, Value>) -> String {
+        let temp_id = uuid::Uuid::new_v4().to_string();
+        self.push(kind, Some(temp_id.clone()), Value::Object(args));
+        temp_id
+    }
+
+    fn push(&mut self, kind: &'static str, temp_id: Option<String>, args: Value) {
+        self.commands.push(Command {
+            kind,
+            uuid: uuid::Uuid::new_v4().to_string(),
+            temp_id,
+            args,
+        });
+    }
+}
+
+
+/// The response to a request with commands.
+#[derive(Debug, Deserialize)]
+struct CommandsResponse {
+    sync_status: HashMap<String, CommandStatus>,
+    #[serde(default)]
+    temp_id_mapping: HashMap<String, String>,
+}
+
+/// The status of a single command, which is either an error or (normally) the string "ok".
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum CommandStatus {
+    Error(CommandError),
+    Ok(IgnoredAny),
+}
+
+impl BatchResults {
+    /// Add the results of a request with the given commands.
+    fn extend(&mut self, commands: &[Command], mut response: CommandsResponse) {
+        for command in commands {
+            let error = match response.sync_status.remove(&command.uuid) {
+                Some(CommandStatus::Ok(_)) => None,
+                Some(CommandStatus::Error(error)) => Some(error),
+                None => Some(CommandError {
+                    error_code: 0,
+                    error: "Todoist didn't return a status for this command".to_string(),
+                }),
+            };
+            self.results.push(CommandResult {
+                uuid: command.uuid.clone(),
+                temp_id: command.temp_id.clone(),
+                error,
+            });
+        }
+        self.temp_id_mapping.extend(response.temp_id_mapping);
+    }
+
+    /// Give the results the temporary IDs the batch returned from its `add_` methods, where the
+    /// commands were sent with others.
+    fn restore_temp_ids(&mut self, commands: &[Command], sent: &[Command]) {
+        for ((command, sent), result) in commands.iter().zip(sent).zip(&mut self.results) {
+            if let (Some(temp_id), Some(sent_temp_id)) = (&command.temp_id, &sent.temp_id) {
+                if temp_id != sent_temp_id {
+                    if let Some(real_id) = self.temp_id_mapping.remove(sent_temp_id) {
+                        self.temp_id_mapping.insert(temp_id.clone(), real_id);
+                    }
+                    result.temp_id = Some(temp_id.clone());
+                }
+            }
+        }
+    }
+}
+
+
+/// Build the request for the chunk of commands with the given index, replacing temporary IDs which
+/// are already known. Every chunk after the first is marked as a separate part of the call, so
+/// that it gets its own request ID even from a client with a pinned one.
+fn commands_request(index: usize, commands: &[Command], temp_id_mapping: &HashMap<String, String>) -> ApiRequest {
+    let commands = commands.iter()
+        .map(|command| {
+            let mut args = command.args.clone();
+            replace_temp_ids(&mut args, temp_id_mapping);
+            let mut value = json!({"type": command.kind, "uuid": command.uuid, "args": args});
+            if let Some(temp_id) = &command.temp_id {
+                value["temp_id"] = json!(temp_id);
+            }
+            value
+        })
+        .collect::<Vec<_>>();
+    let request = ApiRequest::sync_post("/sync").form(&[("commands", &Value::Array(commands).to_string())]);
+    match index {
+        0 => request,
+        index => request.part(&index.to_string()),
+    }
+}
+
+/// Replace every string in the value which is a known temporary ID with the real ID.
+fn replace_temp_ids(value: &mut Value, temp_id_mapping: &HashMap<String, String>) {
+    match value {
+        Value::String(id) => {
+            if let Some(real_id) = temp_id_mapping.get(id.as_str()) {
+                *id = real_id.clone();
+            }
+        }
+        Value::Array(values) => values.iter_mut()
+            .for_each(|value| replace_temp_ids(value, temp_id_mapping)),
+        Value::Object(values) => values.values_mut()
+            .for_each(|value| replace_temp_ids(value, temp_id_mapping)),
+        _ => {}
+    }
+}
+
+/// Add an argument to a command if it is given.
+fn insert<T: serde::Serialize>(args: &mut Map<String, Value>, name: &str, value: &Option<T>) {
+    if let Some(value) = value {
+        args.insert(name.to_string(), json!(value));
+    }
+}
+
+/// The Sync API's form of a due date.
+fn due_args(due: &NewDue) -> Value {
+    match due {
+        NewDue::String { due_string, due_lang } => json!({"string": due_string, "lang": due_lang}),
+        NewDue::Date { due_date } => json!({"date": due_date}),
+        NewDue::Datetime { due_datetime } => json!({"date": due_datetime}),
+    }
+}
+
+/// The Sync API's form of a duration.
+fn duration_args(duration: &NewDuration) -> Value {
+    let unit = match duration.duration_unit {
+        DurationUnit::Minutes => "minute",
+        DurationUnit::Days => "day",
+    };
+    json!({"amount": duration.duration, "unit": unit})
+}
+
+/// The Sync API's arguments for a kind of reminder.
+fn reminder_args(kind: &ReminderKind) -> Map<String, Value> {
+    let args = match kind {
+        ReminderKind::Relative { minutes_before } => json!({"type": "relative", "minute_offset": minutes_before}),
+        ReminderKind::Absolute { datetime } => json!({"type": "absolute", "due": {"date": datetime}}),
+        ReminderKind::Location { name, latitude, longitude, trigger, radius } => {
+            let mut args = json!({
+                "type": "location",
+                "name": name,
+                "loc_lat": latitude.to_string(),
+                "loc_long": longitude.to_string(),
+                "loc_trigger": trigger,
+            });
+            if let Some(radius) = radius {
+                args["radius"] = json!(radius);
+            }
+            args
+        }
+    };
+    match args {
+        Value::Object(args) => args,
+        _ => unreachable!("Reminder arguments are always built from object literals"),
+    }
+}
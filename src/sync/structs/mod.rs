@@ -0,0 +1,11 @@
+/// Defines the `SyncChanges` and `DeletedIds` structs.
+pub mod sync_changes;
+
+/// Defines the objects as the Sync API returns them, and their conversion to the crate's types.
+pub mod resources;
+
+/// Defines the `CommandBatch` struct, for sending write commands.
+pub mod command_batch;
+
+/// Defines the `BatchResults` struct and the types used within it.
+pub mod batch_results;
@@ -0,0 +1,270 @@
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::color::Color;
+use crate::comments::{Attachment, Comment};
+use crate::labels::Label;
+use crate::projects::{Project, ViewStyle};
+use crate::sections::Section;
+use crate::sync::{DeletedIds, SyncChanges};
+use crate::tasks::{Due, Duration, Task};
+
+/// The response to a read request to the `/sync` endpoint.
+#[derive(Debug, Deserialize)]
+pub(crate) struct SyncResponse {
+    sync_token: String,
+    full_sync: bool,
+    #[serde(default)]
+    items: Vec<Value>,
+    #[serde(default)]
+    projects: Vec<Value>,
+    #[serde(default)]
+    sections: Vec<Value>,
+    #[serde(default)]
+    labels: Vec<Value>,
+    #[serde(default)]
+    notes: Vec<Value>,
+    #[serde(default)]
+    project_notes: Vec<Value>,
+}
+
+impl SyncResponse {
+    /// Convert every object in the response to the crate's own types, separating out the ones
+    /// which have been deleted.
+    pub(crate) fn into_changes(self) -> Result<SyncChanges, serde_json::Error> {
+        let mut deleted = DeletedIds::default();
+        let tasks = split::<SyncItem>(self.items, &mut deleted.tasks)?;
+        let projects = split::<SyncProject>(self.projects, &mut deleted.projects)?;
+        let sections = split::<SyncSection>(self.sections, &mut deleted.sections)?;
+        let labels = split::<SyncLabel>(self.labels, &mut deleted.labels)?;
+        let mut comments = split::<SyncNote>(self.notes, &mut deleted.comments)?;
+        comments.extend(split::<SyncNote>(self.project_notes, &mut deleted.comments)?);
+
+        Ok(SyncChanges {
+            sync_token: self.sync_token,
+            full_sync: self.full_sync,
+            tasks: tasks.into_iter().map(Task::from).collect(),
+            projects: projects.into_iter().map(Project::from).collect(),
+            sections: sections.into_iter().map(Section::from).collect(),
+            labels: labels.into_iter().map(Label::from).collect(),
+            comments: comments.into_iter().map(Comment::from).collect(),
+            deleted,
+        })
+    }
+}
+
+/// Deserialise the objects which haven't been deleted, and add the IDs of those which have to
+/// `deleted`. Deleted objects aren't deserialised, since Todoist may leave out their other fields.
+fn split<T: DeserializeOwned>(objects: Vec<Value>, deleted: &mut Vec<String>) -> Result<Vec<T>, serde_json::Error> {
+    let mut kept = Vec::new();
+    for object in objects {
+        if object["is_deleted"].as_bool() == Some(true) {
+            if let Some(id) = object["id"].as_str() {
+                deleted.push(id.to_string());
+            }
+        } else {
+            kept.push(serde_json::from_value(object)?);
+        }
+    }
+    Ok(kept)
+}
+
+
+/// A task, which the Sync API calls an item.
+#[derive(Debug, Deserialize)]
+pub(crate) struct SyncItem {
+    id: String,
+    project_id: String,
+    section_id: Option<String>,
+    content: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    checked: bool,
+    #[serde(default)]
+    labels: Vec<String>,
+    parent_id: Option<String>,
+    #[serde(default)]
+    child_order: i32,
+    #[serde(default = "default_priority")]
+    priority: u8,
+    due: Option<SyncDue>,
+    #[serde(default)]
+    added_at: String,
+    #[serde(default)]
+    added_by_uid: Option<String>,
+    responsible_uid: Option<String>,
+    assigned_by_uid: Option<String>,
+    duration: Option<Duration>,
+}
+
+fn default_priority() -> u8 {
+    1
+}
+
+impl From<SyncItem> for Task {
+    fn from(item: SyncItem) -> Task {
+        Task {
+            url: format!("https://todoist.com/showTask?id={}", item.id),
+            id: item.id,
+            project_id: item.project_id,
+            section_id: item.section_id,
+            content: item.content,
+            description: item.description,
+            is_completed: item.checked,
+            labels: item.labels,
+            parent_id: item.parent_id,
+            order: item.child_order,
+            priority: item.priority,
+            due: item.due.map(Due::from),
+            comment_count: 0,
+            created_at: item.added_at,
+            creator_id: item.added_by_uid.unwrap_or_default(),
+            assignee_id: item.responsible_uid,
+            assigner_id: item.assigned_by_uid,
+            duration: item.duration,
+        }
+    }
+}
+
+
+/// A due date, which the Sync API gives with the date and time (if any) in a single field.
+#[derive(Debug, Deserialize)]
+pub(crate) struct SyncDue {
+    date: String,
+    timezone: Option<String>,
+    #[serde(default)]
+    string: String,
+    #[serde(default)]
+    is_recurring: bool,
+}
+
+impl From<SyncDue> for Due {
+    fn from(due: SyncDue) -> Due {
+        // A date with a time looks like "2016-12-01T12:00:00Z", and one without like "2016-12-01"
+        let (date, datetime) = match due.date.split_once('T') {
+            Some((date, _)) => (date.to_string(), Some(due.date.clone())),
+            None => (due.date, None),
+        };
+        Due {
+            string: due.string,
+            date,
+            is_recurring: due.is_recurring,
+            datetime,
+            timezone: due.timezone,
+        }
+    }
+}
+
+
+/// A project as the Sync API returns it.
+#[derive(Debug, Deserialize)]
+pub(crate) struct SyncProject {
+    id: String,
+    name: String,
+    color: Color,
+    parent_id: Option<String>,
+    #[serde(default)]
+    child_order: i32,
+    #[serde(default)]
+    shared: bool,
+    #[serde(default)]
+    is_favorite: bool,
+    #[serde(default)]
+    inbox_project: bool,
+    view_style: Option<String>,
+}
+
+impl From<SyncProject> for Project {
+    fn from(project: SyncProject) -> Project {
+        Project {
+            url: format!("https://todoist.com/showProject?id={}", project.id),
+            id: project.id,
+            name: project.name,
+            color: project.color,
+            parent_id: project.parent_id,
+            order: project.child_order,
+            comment_count: 0,
+            is_shared: project.shared,
+            is_favorite: project.is_favorite,
+            is_inbox_project: project.inbox_project,
+            view_style: ViewStyle::from_str(project.view_style.as_deref().unwrap_or_default()),
+        }
+    }
+}
+
+
+/// A section as the Sync API returns it.
+#[derive(Debug, Deserialize)]
+pub(crate) struct SyncSection {
+    id: String,
+    project_id: String,
+    #[serde(default)]
+    section_order: i32,
+    name: String,
+}
+
+impl From<SyncSection> for Section {
+    fn from(section: SyncSection) -> Section {
+        Section {
+            id: section.id,
+            project_id: section.project_id,
+            order: section.section_order,
+            name: section.name,
+        }
+    }
+}
+
+
+/// A personal label as the Sync API returns it.
+#[derive(Debug, Deserialize)]
+pub(crate) struct SyncLabel {
+    id: String,
+    name: String,
+    color: Color,
+    #[serde(default)]
+    item_order: i32,
+    #[serde(default)]
+    is_favorite: bool,
+}
+
+impl From<SyncLabel> for Label {
+    fn from(label: SyncLabel) -> Label {
+        Label {
+            id: label.id,
+            name: label.name,
+            color: label.color,
+            order: label.item_order,
+            is_favorite: label.is_favorite,
+        }
+    }
+}
+
+
+/// A comment, which the Sync API calls a note. Notes on tasks have an `item_id`, and notes on
+/// projects a `project_id`.
+#[derive(Debug, Deserialize)]
+pub(crate) struct SyncNote {
+    id: String,
+    item_id: Option<String>,
+    project_id: Option<String>,
+    #[serde(default)]
+    posted_at: String,
+    #[serde(default)]
+    content: String,
+    file_attachment: Option<Attachment>,
+}
+
+impl From<SyncNote> for Comment {
+    fn from(note: SyncNote) -> Comment {
+        Comment {
+            id: note.id,
+            task_id: note.item_id,
+            project_id: note.project_id,
+            posted_at: note.posted_at,
+            content: note.content,
+            attachment: note.file_attachment,
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use crate::comments::Comment;
+use crate::labels::Label;
+use crate::projects::Project;
+use crate::sections::Section;
+use crate::tasks::Task;
+
+/// Everything returned by a single sync: after a full sync, all of the user's objects, and after
+/// an incremental sync, only the objects which have been added or changed since the sync token it
+/// was given, along with the IDs of those which have been deleted.
+///
+/// Objects are returned as the same types the rest of the crate uses. The Sync API doesn't count
+/// comments, so `comment_count` is always 0 for tasks and projects returned by a sync.
+#[derive(Debug)]
+pub struct SyncChanges {
+    /// The token to give to the next `incremental_sync()`, to get only what changes after this
+    /// sync.
+    pub sync_token: String,
+
+    /// Whether this was a full sync. Todoist may answer an incremental sync with a full one (e.g.
+    /// if the sync token is too old), in which case anything stored from earlier syncs should be
+    /// replaced rather than updated.
+    pub full_sync: bool,
+
+    /// Tasks which were added or changed. Unlike the REST API, this includes tasks which have
+    /// been completed since the last sync, with `is_completed` set.
+    pub tasks: Vec<Task>,
+
+    /// Projects which were added or changed.
+    pub projects: Vec<Project>,
+
+    /// Sections which were added or changed.
+    pub sections: Vec<Section>,
+
+    /// Personal labels which were added or changed.
+    pub labels: Vec<Label>,
+
+    /// Comments on tasks or projects which were added or changed.
+    pub comments: Vec<Comment>,
+
+    /// The IDs of objects which were deleted.
+    pub deleted: DeletedIds,
+}
+
+
+/// The IDs of the objects of each type deleted since the last sync.
+#[derive(Debug, Default)]
+#[allow(missing_docs)]
+pub struct DeletedIds {
+    pub tasks: Vec<String>,
+    pub projects: Vec<String>,
+    pub sections: Vec<String>,
+    pub labels: Vec<String>,
+    pub comments: Vec<String>,
+}
@@ -6,13 +6,21 @@
 //!   (`tasks::get_active_tasks_by...`)
 //! - Get an individual task by its ID (`task::get_individual_task_by_id()`)
 //! - Create a new task (`tasks::NewTask::upload()`)
+//! - Create a new task from text in natural language, parsed as the Todoist apps parse it
+//!   (`tasks::quick_add()`)
 //! - Update an existing task (`tasks::UpdateTask::upload()`)
 //! - Delete a task (`project::delete_task_by_id()`)
 //! - Close/complete a task (`project::close_task_by_id()`)
 //! - Reopen a closed task (`project::reopen_task_by_id()`)
+//! - Move a task and its subtasks to another project, section or parent (`tasks::move_task()`)
+//! - Assign a task to a collaborator on its project, by email address or name
+//!   (`tasks::assign_task_by_email()` or `tasks::assign_task_by_name()`)
+//! - Get completed tasks, filtered by project and completion date (`tasks::get_completed_tasks()`,
+//!   or `tasks::get_all_completed_tasks()` for every page of them)
 //!
-//! The first two return a `Vec` of `Task` structs. The next three each return a single `Task`.
-//! The final ones just return `()` in the case of success.
+//! The first two return a `Vec` of `Task` structs. The next four each return a single `Task`.
+//! The next ones just return `()` in the case of success. Completed tasks are returned as
+//! `CompletedTask` records, which say when the task was completed as well as holding the `Task`.
 //!
 //! Creating a new task, or updating an existing one, is done by creating an instance of
 //! `NewTask` or `UpdateTask` respectively. Optional fields are represented by `Option` types.
@@ -22,15 +30,24 @@
 /// Define the different kinds of structs used to represent/handle tasks.
 mod structs;
 
-use std::collections::HashMap;
-use serde_json::Value;
 use crate::err::TodoistAPIError;
-use crate::general::{get_from_reqwest_response, get_204_from_reqwest_response};
+use crate::general::{get_from_response, get_204_from_response, ApiRequest};
+use crate::projects::{self, Collaborator};
+use crate::sync::{CommandBatch, SyncItem};
 use crate::TodoistUser;
+#[cfg(feature = "async")]
+use crate::AsyncTodoistClient;
 
 pub use structs::task::{Task, Due, Duration};
-pub use structs::new_task::{NewTask, NewDue, NewDuration};
+pub use structs::new_task::{NewTask, NewDue, NewDuration, DurationUnit};
 pub use structs::update_task::UpdateTask;
+pub use structs::completed_task::{CompletedTask, CompletedTasksQuery};
+pub use structs::task_destination::TaskDestination;
+pub use structs::quick_add_options::QuickAddOptions;
+use structs::completed_task::CompletedTasksResponse;
+
+/// The most completed tasks Todoist returns in one page.
+pub const COMPLETED_PAGE_LIMIT: u32 = 200;
 
 /// Return a Vec of all the user's active tasks, optionally filtered down.
 ///
@@ -49,7 +66,7 @@ pub use structs::update_task::UpdateTask;
 /// - `filter` - A filter string (see <https://todoist.com/help/articles/introduction-to-filters-V98wIH>).
 /// - `lang` - The language to use for the filter, if not English.
 /// - `ids` - An explicit list of task IDs to return.
-/// `label` - Only return tasks with this label.
+/// - `label` - Only return tasks with this label.
 /// - `project_id` - Only return tasks from this project.
 /// - `section_id` - Only return tasks from this section.
 ///
@@ -72,37 +89,62 @@ fn get_active_tasks_pure(
     project_id: Option<&str>,
     section_id: Option<&str>,
 ) -> Result<Vec<Task>, TodoistAPIError> {
+    // Make the API request
+    let request = get_active_tasks_request(filter, lang, ids, label, project_id, section_id);
+    let response = user.send(request);
+
+    // Now interpret this response properly
+    get_from_response(response)
+}
+
+
+/// Asynchronous version of `get_active_tasks_pure()`.
+#[cfg(feature = "async")]
+async fn get_active_tasks_pure_async(
+    client: &AsyncTodoistClient,
+    filter: Option<&str>,
+    lang: Option<&str>,
+    ids: Option<Vec<&str>>,
+    label: Option<&str>,
+    project_id: Option<&str>,
+    section_id: Option<&str>,
+) -> Result<Vec<Task>, TodoistAPIError> {
+    let request = get_active_tasks_request(filter, lang, ids, label, project_id, section_id);
+    let response = client.send(request).await;
+    get_from_response(response)
+}
+
+
+/// Build the request for `get_active_tasks_pure()` and its async version.
+fn get_active_tasks_request(
+    filter: Option<&str>,
+    lang: Option<&str>,
+    ids: Option<Vec<&str>>,
+    label: Option<&str>,
+    project_id: Option<&str>,
+    section_id: Option<&str>,
+) -> ApiRequest {
     // Construct the arguments of the request
-    let mut args = HashMap::new();
+    let mut request = ApiRequest::get("/tasks");
     if let Some(filter) = filter {
-        args.insert("filter".to_string(), Value::String(filter.to_string()));
+        request = request.query("filter", filter);
     }
     if let Some(lang) = lang {
-        args.insert("lang".to_string(), Value::String(lang.to_string()));
+        request = request.query("lang", lang);
     }
     if let Some(ids) = ids {
-        args.insert("ids".to_string(), Value::String(ids.join(",")));
+        request = request.query("ids", &ids.join(","));
     }
     if let Some(project_id) = project_id {
-        args.insert("project_id".to_string(), Value::String(project_id.to_string()));
+        request = request.query("project_id", project_id);
     }
     if let Some(section_id) = section_id {
-        args.insert("section_id".to_string(), Value::String(section_id.to_string()));
+        request = request.query("section_id", section_id);
     }
     if let Some(label) = label {
-        args.insert("label".to_string(), Value::String(label.to_string()));
+        request = request.query("label", label);
     }
-
-    // Make the API request
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .get("https://api.todoist.com/rest/v2/tasks")
-        .header("Authorization", String::from("Bearer ") + &user.token)
-        .query(&args)
-        .send();
-
-    // Now interpret this response properly
-    get_from_reqwest_response(response)
+    request
 }
 
 
@@ -115,6 +157,15 @@ pub fn get_active_tasks_filtered(
     get_active_tasks_pure(user, Some(filter), None, None, None, None, None)
 }
 
+/// Asynchronous version of `get_active_tasks_filtered()`.
+#[cfg(feature = "async")]
+pub async fn get_active_tasks_filtered_async(
+    client: &AsyncTodoistClient,
+    filter: &str) -> Result<Vec<Task>, TodoistAPIError>
+{
+    get_active_tasks_pure_async(client, Some(filter), None, None, None, None, None).await
+}
+
 
 /// Get all the user's active tasks which match the given string filter, written in the given language.
 ///
@@ -128,6 +179,16 @@ pub fn get_active_tasks_filtered_non_english(
     get_active_tasks_pure(user, Some(filter), Some(lang), None, None, None, None)
 }
 
+/// Asynchronous version of `get_active_tasks_filtered_non_english()`.
+#[cfg(feature = "async")]
+pub async fn get_active_tasks_filtered_non_english_async(
+    client: &AsyncTodoistClient,
+    filter: &str,
+    lang: &str) -> Result<Vec<Task>, TodoistAPIError>
+{
+    get_active_tasks_pure_async(client, Some(filter), Some(lang), None, None, None, None).await
+}
+
 
 /// Get all the tasks with an ID in this list.
 pub fn get_active_tasks_by_id(
@@ -137,6 +198,15 @@ pub fn get_active_tasks_by_id(
     get_active_tasks_pure(user, None, None, Some(ids), None, None, None)
 }
 
+/// Asynchronous version of `get_active_tasks_by_id()`.
+#[cfg(feature = "async")]
+pub async fn get_active_tasks_by_id_async(
+    client: &AsyncTodoistClient,
+    ids: Vec<&str>) -> Result<Vec<Task>, TodoistAPIError>
+{
+    get_active_tasks_pure_async(client, None, None, Some(ids), None, None, None).await
+}
+
 
 /// Get all the user's active tasks which are in the given project.
 pub fn get_active_tasks_by_project(
@@ -146,6 +216,15 @@ pub fn get_active_tasks_by_project(
     get_active_tasks_pure(user, None, None, None, None, Some(project_id), None)
 }
 
+/// Asynchronous version of `get_active_tasks_by_project()`.
+#[cfg(feature = "async")]
+pub async fn get_active_tasks_by_project_async(
+    client: &AsyncTodoistClient,
+    project_id: &str) -> Result<Vec<Task>, TodoistAPIError>
+{
+    get_active_tasks_pure_async(client, None, None, None, None, Some(project_id), None).await
+}
+
 
 /// Get all the user's active tasks which are in the given section.
 pub fn get_active_tasks_by_section(
@@ -155,6 +234,15 @@ pub fn get_active_tasks_by_section(
     get_active_tasks_pure(user, None, None, None, None, None, Some(section_id))
 }
 
+/// Asynchronous version of `get_active_tasks_by_section()`.
+#[cfg(feature = "async")]
+pub async fn get_active_tasks_by_section_async(
+    client: &AsyncTodoistClient,
+    section_id: &str) -> Result<Vec<Task>, TodoistAPIError>
+{
+    get_active_tasks_pure_async(client, None, None, None, None, None, Some(section_id)).await
+}
+
 
 /// Get all the user's active tasks which have the given label.
 pub fn get_active_tasks_by_label(
@@ -164,12 +252,27 @@ pub fn get_active_tasks_by_label(
     get_active_tasks_pure(user, None, None, None, Some(label), None, None)
 }
 
+/// Asynchronous version of `get_active_tasks_by_label()`.
+#[cfg(feature = "async")]
+pub async fn get_active_tasks_by_label_async(
+    client: &AsyncTodoistClient,
+    label: &str) -> Result<Vec<Task>, TodoistAPIError>
+{
+    get_active_tasks_pure_async(client, None, None, None, Some(label), None, None).await
+}
+
 
 /// Get all the user's active tasks.
 pub fn get_all_active_tasks(user: &TodoistUser) -> Result<Vec<Task>, TodoistAPIError> {
     get_active_tasks_pure(user, None, None, None, None, None, None)
 }
 
+/// Asynchronous version of `get_all_active_tasks()`.
+#[cfg(feature = "async")]
+pub async fn get_all_active_tasks_async(client: &AsyncTodoistClient) -> Result<Vec<Task>, TodoistAPIError> {
+    get_active_tasks_pure_async(client, None, None, None, None, None, None).await
+}
+
 
 /// Get the individual task with the given ID.
 pub fn get_individual_task_by_id(
@@ -177,12 +280,20 @@ pub fn get_individual_task_by_id(
     id: &str
 ) -> Result<Task, TodoistAPIError> {
     // Make the API request and interpret the response
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .get(format!("https://api.todoist.com/rest/v2/tasks/{}", id))
-        .header("Authorization", String::from("Bearer ") + &user.token)
-        .send();
-    get_from_reqwest_response(response)
+    let response = user.send(ApiRequest::get(&format!("/tasks/{}", id)).resource("task", id));
+    get_from_response(response)
+}
+
+/// Asynchronous version of `get_individual_task_by_id()`.
+#[cfg(feature = "async")]
+pub async fn get_individual_task_by_id_async(
+    client: &AsyncTodoistClient,
+    id: &str
+) -> Result<Task, TodoistAPIError> {
+    let request = ApiRequest::get(&format!("/tasks/{}", id))
+        .resource("task", id);
+    let response = client.send(request).await;
+    get_from_response(response)
 }
 
 
@@ -192,12 +303,22 @@ pub fn close_task_by_id(
     id: &str,
 ) -> Result<(), TodoistAPIError> {
     // Make the API request and interpret the response
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .post(format!("https://api.todoist.com/rest/v2/tasks/{}/close", id))
-        .header("Authorization", String::from("Bearer ") + &user.token)
-        .send();
-    get_204_from_reqwest_response(response)
+    let request = ApiRequest::post(&format!("/tasks/{}/close", id))
+        .resource("task", id);
+    let response = user.send(request);
+    get_204_from_response(response)
+}
+
+/// Asynchronous version of `close_task_by_id()`.
+#[cfg(feature = "async")]
+pub async fn close_task_by_id_async(
+    client: &AsyncTodoistClient,
+    id: &str,
+) -> Result<(), TodoistAPIError> {
+    let request = ApiRequest::post(&format!("/tasks/{}/close", id))
+        .resource("task", id);
+    let response = client.send(request).await;
+    get_204_from_response(response)
 }
 
 
@@ -207,10 +328,228 @@ pub fn reopen_task_by_id(
     id: &str,
 ) -> Result<(), TodoistAPIError> {
     // Make the API request and interpret the response
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .post(format!("https://api.todoist.com/rest/v2/tasks/{}/reopen", id))
-        .header("Authorization", String::from("Bearer ") + &user.token)
-        .send();
-    get_204_from_reqwest_response(response)
+    let request = ApiRequest::post(&format!("/tasks/{}/reopen", id))
+        .resource("task", id);
+    let response = user.send(request);
+    get_204_from_response(response)
+}
+
+/// Asynchronous version of `reopen_task_by_id()`.
+#[cfg(feature = "async")]
+pub async fn reopen_task_by_id_async(
+    client: &AsyncTodoistClient,
+    id: &str,
+) -> Result<(), TodoistAPIError> {
+    let request = ApiRequest::post(&format!("/tasks/{}/reopen", id))
+        .resource("task", id);
+    let response = client.send(request).await;
+    get_204_from_response(response)
+}
+
+
+/// Create a task from text such as "Pay rent every 1st #Home @finance p1", which Todoist parses
+/// exactly as its Quick Add box does: the project, labels, priority and due date are taken out of
+/// the text, and what is left becomes the task's content. Returns the new task.
+pub fn quick_add(user: &TodoistUser, text: &str, options: &QuickAddOptions) -> Result<Task, TodoistAPIError> {
+    // Send the API request
+    let response = user.send(options.request(text));
+    get_from_response::<SyncItem>(response).map(Task::from)
+}
+
+/// Asynchronous version of `quick_add()`.
+#[cfg(feature = "async")]
+pub async fn quick_add_async(client: &AsyncTodoistClient, text: &str, options: &QuickAddOptions) -> Result<Task, TodoistAPIError> {
+    let response = client.send(options.request(text)).await;
+    get_from_response::<SyncItem>(response).map(Task::from)
+}
+
+
+/// Move a task, along with its subtasks, to another project, section or parent task, returning
+/// the moved task.
+///
+/// The REST API can't move tasks, so this is done with the Sync API's `item_move` command. If
+/// Todoist rejects it (e.g. because the destination doesn't exist), returns a `CommandError`.
+pub fn move_task(user: &TodoistUser, task_id: &str, destination: &TaskDestination) -> Result<Task, TodoistAPIError> {
+    let mut batch = CommandBatch::new();
+    batch.move_task(task_id, destination);
+    batch.submit(user)?.into_result()?;
+    get_individual_task_by_id(user, task_id)
+}
+
+/// Asynchronous version of `move_task()`.
+#[cfg(feature = "async")]
+pub async fn move_task_async(client: &AsyncTodoistClient, task_id: &str, destination: &TaskDestination) -> Result<Task, TodoistAPIError> {
+    let mut batch = CommandBatch::new();
+    batch.move_task(task_id, destination);
+    batch.submit_async(client).await?.into_result()?;
+    get_individual_task_by_id_async(client, task_id).await
+}
+
+
+/// Assign a task to the collaborator on its project with the given email address (ignoring
+/// case), returning the updated task. If nobody on the project has that address, returns a
+/// `CollaboratorNotFound` error.
+pub fn assign_task_by_email(user: &TodoistUser, task_id: &str, email: &str) -> Result<Task, TodoistAPIError> {
+    let task = get_individual_task_by_id(user, task_id)?;
+    let collaborators = projects::get_project_collaborators(user, &task.project_id)?;
+    let assignee = collaborator_by_email(&collaborators, &task.project_id, email)?;
+    assignment(assignee).upload(user, task_id)
+}
+
+/// Asynchronous version of `assign_task_by_email()`.
+#[cfg(feature = "async")]
+pub async fn assign_task_by_email_async(client: &AsyncTodoistClient, task_id: &str, email: &str) -> Result<Task, TodoistAPIError> {
+    let task = get_individual_task_by_id_async(client, task_id).await?;
+    let collaborators = projects::get_project_collaborators_async(client, &task.project_id).await?;
+    let assignee = collaborator_by_email(&collaborators, &task.project_id, email)?;
+    assignment(assignee).upload_async(client, task_id).await
+}
+
+
+/// Assign a task to the collaborator on its project with the given name (ignoring case),
+/// returning the updated task. If nobody on the project has that name, returns a
+/// `CollaboratorNotFound` error, and if more than one person does, an `AmbiguousCollaborator`
+/// error.
+pub fn assign_task_by_name(user: &TodoistUser, task_id: &str, name: &str) -> Result<Task, TodoistAPIError> {
+    let task = get_individual_task_by_id(user, task_id)?;
+    let collaborators = projects::get_project_collaborators(user, &task.project_id)?;
+    let assignee = collaborator_by_name(&collaborators, &task.project_id, name)?;
+    assignment(assignee).upload(user, task_id)
+}
+
+/// Asynchronous version of `assign_task_by_name()`.
+#[cfg(feature = "async")]
+pub async fn assign_task_by_name_async(client: &AsyncTodoistClient, task_id: &str, name: &str) -> Result<Task, TodoistAPIError> {
+    let task = get_individual_task_by_id_async(client, task_id).await?;
+    let collaborators = projects::get_project_collaborators_async(client, &task.project_id).await?;
+    let assignee = collaborator_by_name(&collaborators, &task.project_id, name)?;
+    assignment(assignee).upload_async(client, task_id).await
+}
+
+/// Find the collaborator with the given email address.
+fn collaborator_by_email<'a>(
+    collaborators: &'a [Collaborator],
+    project_id: &str,
+    email: &str,
+) -> Result<&'a Collaborator, TodoistAPIError> {
+    collaborators.iter()
+        .find(|collaborator| collaborator.email.eq_ignore_ascii_case(email.trim()))
+        .ok_or_else(|| TodoistAPIError::CollaboratorNotFound {
+            project_id: project_id.to_string(),
+            person: email.to_string(),
+        })
+}
+
+/// Find the only collaborator with the given name.
+fn collaborator_by_name<'a>(
+    collaborators: &'a [Collaborator],
+    project_id: &str,
+    name: &str,
+) -> Result<&'a Collaborator, TodoistAPIError> {
+    let matches = collaborators.iter()
+        .filter(|collaborator| collaborator.name.to_lowercase() == name.trim().to_lowercase())
+        .collect::<Vec<_>>();
+    match matches.as_slice() {
+        [collaborator] => Ok(collaborator),
+        [] => Err(TodoistAPIError::CollaboratorNotFound {
+            project_id: project_id.to_string(),
+            person: name.to_string(),
+        }),
+        _ => Err(TodoistAPIError::AmbiguousCollaborator {
+            project_id: project_id.to_string(),
+            name: name.to_string(),
+            ids: matches.iter().map(|collaborator| collaborator.id.clone()).collect(),
+        }),
+    }
+}
+
+/// An update which only assigns the task to the collaborator.
+fn assignment(assignee: &Collaborator) -> UpdateTask {
+    UpdateTask {
+        content: None,
+        description: None,
+        labels: None,
+        priority: None,
+        due: None,
+        assignee_id: Some(assignee.id.clone()),
+        duration: None,
+    }
+}
+
+
+/// Get a page of the user's completed tasks, most recently completed first, filtered and limited
+/// by the query. To get every matching task rather than one page, use
+/// `get_all_completed_tasks()`.
+pub fn get_completed_tasks(
+    user: &TodoistUser,
+    query: &CompletedTasksQuery,
+) -> Result<Vec<CompletedTask>, TodoistAPIError> {
+    // Send the API request
+    let response = user.send(query.request());
+    get_from_response::<CompletedTasksResponse>(response).map(Vec::from)
+}
+
+/// Asynchronous version of `get_completed_tasks()`.
+#[cfg(feature = "async")]
+pub async fn get_completed_tasks_async(
+    client: &AsyncTodoistClient,
+    query: &CompletedTasksQuery,
+) -> Result<Vec<CompletedTask>, TodoistAPIError> {
+    let response = client.send(query.request()).await;
+    get_from_response::<CompletedTasksResponse>(response).map(Vec::from)
+}
+
+
+/// Get every completed task matching the query, requesting one page after another until there
+/// are no more. The query's `limit` is used as the size of each page (200 if it isn't given, and
+/// at most 200, which is all Todoist returns), and its `offset` as where to start.
+pub fn get_all_completed_tasks(
+    user: &TodoistUser,
+    query: &CompletedTasksQuery,
+) -> Result<Vec<CompletedTask>, TodoistAPIError> {
+    let mut query = first_completed_page(query);
+    let mut completed = Vec::new();
+    loop {
+        let page = get_completed_tasks(user, &query)?;
+        if !next_completed_page(&mut query, &mut completed, page) {
+            return Ok(completed);
+        }
+    }
+}
+
+/// Asynchronous version of `get_all_completed_tasks()`.
+#[cfg(feature = "async")]
+pub async fn get_all_completed_tasks_async(
+    client: &AsyncTodoistClient,
+    query: &CompletedTasksQuery,
+) -> Result<Vec<CompletedTask>, TodoistAPIError> {
+    let mut query = first_completed_page(query);
+    let mut completed = Vec::new();
+    loop {
+        let page = get_completed_tasks_async(client, &query).await?;
+        if !next_completed_page(&mut query, &mut completed, page) {
+            return Ok(completed);
+        }
+    }
+}
+
+/// The query for the first page of `get_all_completed_tasks()`.
+fn first_completed_page(query: &CompletedTasksQuery) -> CompletedTasksQuery {
+    CompletedTasksQuery {
+        limit: Some(query.limit.unwrap_or(COMPLETED_PAGE_LIMIT).min(COMPLETED_PAGE_LIMIT)),
+        ..query.clone()
+    }
+}
+
+/// Add a page of results to those so far, and move the query on to the next page. Returns whether
+/// there might be another page, which there isn't if this one wasn't full.
+fn next_completed_page(
+    query: &mut CompletedTasksQuery,
+    completed: &mut Vec<CompletedTask>,
+    page: Vec<CompletedTask>,
+) -> bool {
+    let count = page.len() as u32;
+    completed.extend(page);
+    query.offset = Some(query.offset.unwrap_or(0) + count);
+    count > 0 && Some(count) == query.limit
 }
@@ -0,0 +1,119 @@
+use serde::Deserialize;
+use crate::general::ApiRequest;
+use crate::sync::SyncItem;
+use crate::tasks::Task;
+
+/// A record of a task being completed, as returned by `get_completed_tasks()`.
+#[derive(Debug)]
+pub struct CompletedTask {
+    /// The ID of the completion record. A recurring task has a separate record each time it is
+    /// completed.
+    pub id: String,
+
+    /// The ID of the task which was completed.
+    pub task_id: String,
+
+    /// The content of the task when it was completed.
+    pub content: String,
+
+    /// The ID of the project the task was in.
+    pub project_id: String,
+
+    /// The ID of the section the task was in, if any.
+    pub section_id: Option<String>,
+
+    /// When the task was completed, in RFC3339 format in UTC.
+    pub completed_at: String,
+
+    /// The number of comments on the task.
+    pub note_count: u32,
+
+    /// The task itself, if Todoist still has it. Its fields (including `is_completed`) show the
+    /// task as it is now, which for a recurring task is its next occurrence.
+    pub task: Option<Task>,
+}
+
+
+/// Which completed tasks to return from `get_completed_tasks()`. Every field is optional, so
+/// start from `CompletedTasksQuery::default()` and fill in the ones needed.
+///
+/// Dates are given as `YYYY-MM-DDTHH:MM`, e.g. `2024-01-31T09:00`, in UTC.
+#[derive(Debug, Clone, Default)]
+pub struct CompletedTasksQuery {
+    /// Only return tasks completed in this project.
+    pub project_id: Option<String>,
+
+    /// Only return tasks completed after this date.
+    pub since: Option<String>,
+
+    /// Only return tasks completed before this date.
+    pub until: Option<String>,
+
+    /// The most tasks to return, up to 200. Todoist returns 30 if this isn't given.
+    pub limit: Option<u32>,
+
+    /// How many tasks to skip, for getting later pages of results.
+    pub offset: Option<u32>,
+}
+
+impl CompletedTasksQuery {
+    /// Build the request for the page of completed tasks the query describes.
+    pub(crate) fn request(&self) -> ApiRequest {
+        let mut request = ApiRequest::sync_get("/completed/get_all")
+            .query("annotate_items", "true");
+        if let Some(project_id) = &self.project_id {
+            request = request.query("project_id", project_id);
+        }
+        if let Some(since) = &self.since {
+            request = request.query("since", since);
+        }
+        if let Some(until) = &self.until {
+            request = request.query("until", until);
+        }
+        if let Some(limit) = self.limit {
+            request = request.query("limit", &limit.to_string());
+        }
+        if let Some(offset) = self.offset {
+            request = request.query("offset", &offset.to_string());
+        }
+        request
+    }
+}
+
+
+/// The response to a request for completed tasks.
+#[derive(Debug, Deserialize)]
+pub(crate) struct CompletedTasksResponse {
+    items: Vec<CompletedItem>,
+}
+
+/// A completion record as the Sync API returns it.
+#[derive(Debug, Deserialize)]
+struct CompletedItem {
+    id: String,
+    task_id: String,
+    content: String,
+    project_id: String,
+    section_id: Option<String>,
+    completed_at: String,
+    #[serde(default)]
+    note_count: u32,
+    item_object: Option<SyncItem>,
+}
+
+impl From<CompletedTasksResponse> for Vec<CompletedTask> {
+    fn from(response: CompletedTasksResponse) -> Vec<CompletedTask> {
+        response.items.into_iter()
+            .map(|item| CompletedTask {
+                id: item.id,
+                task_id: item.task_id,
+                content: item.content,
+                project_id: item.project_id,
+                section_id: item.section_id,
+                completed_at: item.completed_at,
+                note_count: item.note_count,
+                task: item.item_object.map(Task::from),
+            })
+            .collect()
+    }
+}
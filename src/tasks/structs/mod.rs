@@ -7,3 +7,12 @@ pub mod new_task;
 
 /// Defines the `UpdateTask` struct and its upload() function
 pub mod update_task;
+
+/// Defines the `CompletedTask` struct, and the `CompletedTasksQuery` used to ask for them.
+pub mod completed_task;
+
+/// Defines the `TaskDestination` enum, which says where `move_task()` should move a task to.
+pub mod task_destination;
+
+/// Defines the `QuickAddOptions` struct, for the extra options given to `quick_add()`.
+pub mod quick_add_options;
@@ -1,8 +1,10 @@
 use serde::{Deserialize, Serialize};
 use crate::err::TodoistAPIError;
-use crate::general::get_from_reqwest_response;
+use crate::general::{get_from_response, ApiRequest};
 use crate::tasks::Task;
 use crate::TodoistUser;
+#[cfg(feature = "async")]
+use crate::AsyncTodoistClient;
 
 /// Used to define the creation of a new task.
 ///
@@ -67,13 +69,15 @@ impl NewTask {
 
     /// Upload this new task to the API.
     pub fn upload(&self, user: &TodoistUser) -> Result<Task, TodoistAPIError> {
-        let client = reqwest::blocking::Client::new();
-        let response = client
-            .post("https://api.todoist.com/rest/v2/tasks")
-            .header("Authorization", "Bearer ".to_string() + &user.token)
-            .json(&self)
-            .send();
-        get_from_reqwest_response(response)
+        let response = user.send(ApiRequest::post("/tasks").json(self)?);
+        get_from_response(response)
+    }
+
+    /// Asynchronous version of `upload()`.
+    #[cfg(feature = "async")]
+    pub async fn upload_async(&self, client: &AsyncTodoistClient) -> Result<Task, TodoistAPIError> {
+        let response = client.send(ApiRequest::post("/tasks").json(self)?).await;
+        get_from_response(response)
     }
 }
 
@@ -82,6 +86,7 @@ impl NewTask {
 /// * A due string and, optionally, a corresponding language (defaulting to english)
 /// * A date in the format `YYYY-MM-DD`, relative to the user's timezone
 /// * A datetime in RFC3339 format, in UTC.
+///
 /// These options are mutually exclusive.
 #[derive(Debug, Serialize)]
 #[serde(untagged)]
@@ -0,0 +1,33 @@
+use crate::general::ApiRequest;
+
+/// The extra options for `quick_add()`. Every field is optional, so start from
+/// `QuickAddOptions::default()` and fill in the ones needed.
+#[derive(Debug, Clone, Default)]
+pub struct QuickAddOptions {
+    /// A comment to add to the new task.
+    pub note: Option<String>,
+
+    /// When to remind the user about the task, in natural language (e.g. "tomorrow at 9am"),
+    /// parsed as Todoist parses due dates.
+    pub reminder: Option<String>,
+
+    /// Whether to add the user's default reminder to the task if it has a due time.
+    pub auto_reminder: bool,
+}
+
+impl QuickAddOptions {
+    /// Build the request to quick add the text with these options.
+    pub(crate) fn request(&self, text: &str) -> ApiRequest {
+        let mut fields = vec![("text", text)];
+        if let Some(note) = &self.note {
+            fields.push(("note", note));
+        }
+        if let Some(reminder) = &self.reminder {
+            fields.push(("reminder", reminder));
+        }
+        if self.auto_reminder {
+            fields.push(("auto_reminder", "true"));
+        }
+        ApiRequest::sync_post("/quick/add").form(&fields)
+    }
+}
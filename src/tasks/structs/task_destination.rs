@@ -0,0 +1,12 @@
+/// Where to move a task to with `move_task()`. Its subtasks are always moved along with it.
+#[derive(Debug, Clone)]
+pub enum TaskDestination {
+    /// To the top level of the project with this ID, outside any section.
+    Project(String),
+
+    /// Into the section with this ID, in whichever project it is in.
+    Section(String),
+
+    /// Under the task with this ID, as a subtask.
+    Parent(String),
+}
@@ -1,8 +1,10 @@
 use serde::Serialize;
 use crate::err::TodoistAPIError;
-use crate::general::get_from_reqwest_response;
+use crate::general::{get_from_response, ApiRequest};
 use crate::tasks::{NewDue, NewDuration, Task};
 use crate::TodoistUser;
+#[cfg(feature = "async")]
+use crate::AsyncTodoistClient;
 
 /// Used to make an update to a task. Any field which is `None` will not be updated.
 ///
@@ -44,12 +46,24 @@ impl UpdateTask {
         task_id: &str,
     ) -> Result<Task, TodoistAPIError> {
         // Make the API request and interpret the response
-        let client = reqwest::blocking::Client::new();
-        let response = client
-            .post(format!("https://api.todoist.com/rest/v2/tasks/{}", task_id))
-            .header("Authorization", String::from("Bearer ") + &user.token)
-            .json(&self)
-            .send();
-        get_from_reqwest_response(response)
+        let request = ApiRequest::post(&format!("/tasks/{}", task_id))
+            .resource("task", task_id)
+            .json(self)?;
+        let response = user.send(request);
+        get_from_response(response)
+    }
+
+    /// Asynchronous version of `upload()`.
+    #[cfg(feature = "async")]
+    pub async fn upload_async(
+        &self,
+        client: &AsyncTodoistClient,
+        task_id: &str,
+    ) -> Result<Task, TodoistAPIError> {
+        let request = ApiRequest::post(&format!("/tasks/{}", task_id))
+            .resource("task", task_id)
+            .json(self)?;
+        let response = client.send(request).await;
+        get_from_response(response)
     }
 }
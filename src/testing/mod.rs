@@ -0,0 +1,198 @@
+//! A fake Todoist server, for running integration tests without network access. Only available
+//! with the `testing` feature.
+//!
+//! `FakeTodoist::start()` starts a server on a free local port, which keeps its own projects,
+//! sections, tasks, labels and comments in memory and responds to the REST v2 endpoints this
+//! crate uses in the same way Todoist does. Each server starts out holding only the user's inbox
+//! project, and is shut down when it is dropped, so every test can have its own.
+//!
+//! `FakeTodoist::client()` gives a client which sends its requests to the server, with the API
+//! token the server accepts:
+//! ```
+//! use todoist_v2_rest::{projects, tasks};
+//! use todoist_v2_rest::testing::FakeTodoist;
+//!
+//! let server = FakeTodoist::start();
+//! let client = server.client();
+//!
+//! let project = projects::NewProject {
+//!     name: "Groceries".to_string(),
+//!     parent_id: None,
+//!     color: None,
+//!     is_favorite: None,
+//!     view_style: None,
+//! }.upload(&client).unwrap();
+//!
+//! let mut new_task = tasks::NewTask::new("Buy milk");
+//! new_task.project_id = Some(project.id.clone());
+//! let task = new_task.upload(&client).unwrap();
+//!
+//! tasks::close_task_by_id(&client, &task.id).unwrap();
+//! assert!(tasks::get_active_tasks_by_project(&client, &project.id).unwrap().is_empty());
+//! ```
+//!
+//! The fake is deliberately simple. It doesn't understand filter queries or natural language due
+//! dates (a `due_string` other than "no date" is taken to mean today, and `tasks::quick_add()` only
+//! picks out projects, labels and priorities), and the only collaborator on any project is the user
+//! themself (`FAKE_USER_NAME`), so it only checks that requests are well-formed rather than that
+//! they would be allowed. Of the Sync API, it only applies the commands the crate uses for things
+//! REST v2 can't do (such as `tasks::move_task()`, saved filters and reminders), rejecting any
+//! others, and a sync only returns filters, reminders and the user's profile (in UTC), always as a
+//! full sync. It also serves the OAuth token endpoints used by the `oauth` module (give the
+//! server's URL to `OAuthApp::oauth_base_url()`), exchanging `FAKE_AUTHORIZATION_CODE` for a new
+//! token. Requests carrying an `X-Request-Id` it has already seen are answered the same way as the
+//! first time, without being applied again, as Todoist does.
+
+mod store;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+use crate::retry::RetryPolicy;
+use crate::TodoistClient;
+#[cfg(feature = "async")]
+use crate::AsyncTodoistClient;
+use store::{FakeRequest, Reply, Store};
+pub use store::{FAKE_USER_EMAIL, FAKE_USER_ID, FAKE_USER_NAME};
+
+/// The API token a `FakeTodoist` server accepts, along with any it has issued through OAuth.
+/// Requests with any other token are rejected with `401 Unauthorized`.
+pub const FAKE_API_TOKEN: &str = "0123456789abcdef0123456789abcdef01234567";
+
+/// The OAuth authorization code a `FakeTodoist` server will exchange for a new access token, with
+/// any client ID and secret. Any other code is rejected with `400 Bad Request`.
+pub const FAKE_AUTHORIZATION_CODE: &str = "fake-authorization-code";
+
+
+/// A fake Todoist server running on a local port, in a background thread.
+pub struct FakeTodoist {
+    server: Arc<tiny_http::Server>,
+    url: String,
+    store: Arc<Mutex<Store>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl FakeTodoist {
+    /// Start a server on a free port of `127.0.0.1`.
+    ///
+    /// # Panics
+    /// If no local port can be bound.
+    pub fn start() -> FakeTodoist {
+        let server = tiny_http::Server::http("127.0.0.1:0").expect("Couldn't start fake Todoist server");
+        let port = server.server_addr().to_ip().expect("Fake Todoist server isn't on an IP address").port();
+        let server = Arc::new(server);
+        let store = Arc::new(Mutex::new(Store::new()));
+
+        let thread = {
+            let server = server.clone();
+            let store = store.clone();
+            std::thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    serve(&store, request);
+                }
+            })
+        };
+
+        FakeTodoist {
+            server,
+            url: format!("http://127.0.0.1:{}", port),
+            store,
+            thread: Some(thread),
+        }
+    }
+
+    /// The base URL of the server, e.g. `http://127.0.0.1:53001`, to give to
+    /// `TodoistClientBuilder::base_url()`.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// A client which sends its requests to this server, using `FAKE_API_TOKEN`. It doesn't retry
+    /// failed requests, so that every error reaches the test straight away.
+    pub fn client(&self) -> TodoistClient {
+        TodoistClient::builder(FAKE_API_TOKEN)
+            .base_url(&self.url)
+            .retry_policy(RetryPolicy::none())
+            .build()
+            .expect("Couldn't build client for fake Todoist server")
+    }
+
+    /// The asynchronous version of `client()`. Only available with the `async` feature.
+    #[cfg(feature = "async")]
+    pub fn async_client(&self) -> AsyncTodoistClient {
+        TodoistClient::builder(FAKE_API_TOKEN)
+            .base_url(&self.url)
+            .retry_policy(RetryPolicy::none())
+            .build_async()
+            .expect("Couldn't build client for fake Todoist server")
+    }
+
+    /// The ID of the user's inbox project, which every server starts out with.
+    pub fn inbox_id(&self) -> String {
+        self.lock().inbox_id()
+    }
+
+    /// Lock the store, ignoring poisoning since a panicking test shouldn't hide other failures.
+    fn lock(&self) -> std::sync::MutexGuard<'_, Store> {
+        self.store.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Drop for FakeTodoist {
+    /// Stop the server and wait for its thread to finish.
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+
+/// Respond to a single HTTP request.
+fn serve(store: &Mutex<Store>, mut request: tiny_http::Request) {
+    let token = header(&request, "Authorization")
+        .and_then(|value| value.strip_prefix("Bearer ").map(String::from));
+    let request_id = header(&request, "X-Request-Id");
+
+    let mut body = Vec::new();
+    let reply = if let Err(err) = request.as_reader().read_to_end(&mut body) {
+        Reply::Error(400, format!("Couldn't read request body: {}", err))
+    } else {
+        // Only the path and query matter, so any host will do for parsing the URL
+        match reqwest::Url::parse(&format!("http://localhost{}", request.url())) {
+            Ok(url) => {
+                let fake_request = FakeRequest {
+                    method: request.method().as_str().to_uppercase(),
+                    path: url.path().to_string(),
+                    query: url.query_pairs().into_owned().collect::<HashMap<_, _>>(),
+                    request_id,
+                    body,
+                    token,
+                };
+                store.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).handle(&fake_request)
+            }
+            Err(err) => Reply::Error(400, format!("Invalid URL: {}", err)),
+        }
+    };
+
+    let (status, content_type, body) = match reply {
+        Reply::Json(status, value) => (status, "application/json", value.to_string().into_bytes()),
+        Reply::Empty => (204, "text/plain", Vec::new()),
+        Reply::Error(status, message) => (status, "text/plain", message.into_bytes()),
+    };
+    let content_type = tiny_http::Header::from_bytes("Content-Type", content_type)
+        .expect("Content-Type is a valid header");
+    let response = tiny_http::Response::from_data(body)
+        .with_status_code(status)
+        .with_header(content_type);
+    let _ = request.respond(response);
+}
+
+/// The value of the named header of the request, if it has it.
+fn header(request: &tiny_http::Request, name: &'static str) -> Option<String> {
+    request.headers().iter()
+        .find(|header| header.field.equiv(name))
+        .map(|header| header.value.as_str().to_string())
+}
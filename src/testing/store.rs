@@ -0,0 +1,1130 @@
+//! The state held by a `FakeTodoist` server, and how each request changes it.
+//!
+//! Objects are kept as JSON maps in the same shape the REST v2 API returns them, except for
+//! `comment_count`, which is worked out whenever an object is returned.
+
+use std::collections::{HashMap, HashSet};
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde_json::{json, Map, Value};
+use super::{FAKE_API_TOKEN, FAKE_AUTHORIZATION_CODE};
+
+type Object = Map<String, Value>;
+
+/// The ID of the (only) user of a fake server, used as the creator of every task.
+pub const FAKE_USER_ID: &str = "2671355";
+
+/// The name of the user of a fake server, who is the only collaborator on every project.
+pub const FAKE_USER_NAME: &str = "Fake User";
+
+/// The email address of the user of a fake server.
+pub const FAKE_USER_EMAIL: &str = "fake.user@example.com";
+
+
+/// A request to the fake server, with the parts the store cares about already extracted.
+pub struct FakeRequest {
+    pub method: String,
+    pub path: String,
+    pub query: HashMap<String, String>,
+    pub request_id: Option<String>,
+    pub body: Vec<u8>,
+
+    /// The bearer token from the `Authorization` header, if there was one.
+    pub token: Option<String>,
+}
+
+
+/// The response the fake server should give.
+#[derive(Clone)]
+pub enum Reply {
+    /// A response with a JSON body.
+    Json(u16, Value),
+
+    /// `204 No Content`.
+    Empty,
+
+    /// An unsuccessful response, with a plain text explanation as Todoist gives.
+    Error(u16, String),
+}
+
+impl Reply {
+    fn bad_request(message: &str) -> Reply {
+        Reply::Error(400, message.to_string())
+    }
+
+    fn not_found(kind: &str) -> Reply {
+        Reply::Error(404, format!("{} not found", kind))
+    }
+}
+
+
+/// Everything stored by a fake server.
+pub struct Store {
+    next_id: u64,
+    projects: Vec<Object>,
+    sections: Vec<Object>,
+    tasks: Vec<Object>,
+    labels: Vec<Object>,
+    comments: Vec<Object>,
+
+    /// Saved filters and reminders, which only the Sync API covers, so are kept in its shape.
+    filters: Vec<Object>,
+    reminders: Vec<Object>,
+
+    /// The IDs of the projects which are archived.
+    archived: HashSet<String>,
+
+    /// The API tokens which are accepted: `FAKE_API_TOKEN`, plus any issued through OAuth and not
+    /// revoked since.
+    tokens: HashSet<String>,
+    issued_tokens: u32,
+
+    /// The replies to successful mutating requests, by their `X-Request-Id`, so that duplicates
+    /// are answered the same way without being applied twice.
+    replies: HashMap<String, Reply>,
+}
+
+impl Store {
+    /// A store holding nothing but the user's inbox project.
+    pub fn new() -> Store {
+        let mut store = Store {
+            next_id: 2203306141,
+            projects: Vec::new(),
+            sections: Vec::new(),
+            tasks: Vec::new(),
+            labels: Vec::new(),
+            comments: Vec::new(),
+            filters: Vec::new(),
+            reminders: Vec::new(),
+            archived: HashSet::new(),
+            tokens: HashSet::from([FAKE_API_TOKEN.to_string()]),
+            issued_tokens: 0,
+            replies: HashMap::new(),
+        };
+        let id = store.new_id();
+        store.projects.push(object(json!({
+            "id": id,
+            "name": "Inbox",
+            "color": "charcoal",
+            "parent_id": null,
+            "order": 0,
+            "is_shared": false,
+            "is_favorite": false,
+            "is_inbox_project": true,
+            "view_style": "list",
+            "url": format!("https://todoist.com/showProject?id={}", id),
+        })));
+        store
+    }
+
+    /// The ID of the inbox project.
+    pub fn inbox_id(&self) -> String {
+        self.projects.iter()
+            .find(|project| project["is_inbox_project"] == true)
+            .map(|project| id_of(project).to_string())
+            .unwrap_or_default()
+    }
+
+    /// Respond to a request, replaying the earlier reply if it has a request ID already seen.
+    pub fn handle(&mut self, request: &FakeRequest) -> Reply {
+        let public = matches!(request.path.as_str(), "/oauth/access_token" | "/sync/v9/access_tokens/revoke");
+        if !public && !request.token.as_ref().is_some_and(|token| self.tokens.contains(token)) {
+            return Reply::Error(401, "Forbidden".to_string());
+        }
+        if let Some(reply) = request.request_id.as_ref().and_then(|id| self.replies.get(id)) {
+            return reply.clone();
+        }
+
+        let reply = match parse_body(&request.body) {
+            Ok(body) => self.route(request, &body),
+            Err(reply) => reply,
+        };
+
+        if let Some(request_id) = &request.request_id {
+            if request.method != "GET" && !matches!(reply, Reply::Error(..)) {
+                self.replies.insert(request_id.clone(), reply.clone());
+            }
+        }
+        reply
+    }
+
+    /// Pass the request to the handler for its method and path.
+    fn route(&mut self, request: &FakeRequest, body: &Value) -> Reply {
+        let result = match (request.method.as_str(), request.path.as_str()) {
+            ("POST", "/oauth/access_token") => Some(self.exchange_code(body)),
+            ("POST", "/sync/v9/access_tokens/revoke") => Some(self.revoke_token(body)),
+            ("POST", "/sync/v9/sync") if body.get("sync_token").is_some() => Some(self.sync_read(body)),
+            ("POST", "/sync/v9/sync") => Some(self.sync_commands(body)),
+            ("GET", "/sync/v9/filters/get") => Some(self.get_filter(&request.query)),
+            ("POST", "/sync/v9/quick/add") => Some(self.quick_add(body)),
+            ("GET", "/sync/v9/projects/get_archived") => Some(Ok(self.list_archived_projects())),
+            _ => None,
+        };
+        if let Some(result) = result {
+            return result.unwrap_or_else(|reply| reply);
+        }
+
+        let path = match request.path.strip_prefix("/rest/v2/") {
+            Some(path) => path.trim_end_matches('/'),
+            None => return Reply::not_found("Path"),
+        };
+        let segments: Vec<&str> = path.split('/').collect();
+        let query = &request.query;
+
+        let result = match (request.method.as_str(), segments.as_slice()) {
+            ("GET", ["projects"]) => Ok(self.list_projects()),
+            ("POST", ["projects"]) => self.create_project(body),
+            ("GET", ["projects", id]) => self.get_project(id),
+            ("POST", ["projects", id]) => self.update_project(id, body),
+            ("DELETE", ["projects", id]) => self.delete_project(id),
+            ("GET", ["projects", id, "collaborators"]) => self.list_collaborators(id),
+
+            ("GET", ["sections"]) => Ok(self.list_sections(query)),
+            ("POST", ["sections"]) => self.create_section(body),
+            ("GET", ["sections", id]) => self.get_section(id),
+            ("POST", ["sections", id]) => self.update_section(id, body),
+            ("DELETE", ["sections", id]) => self.delete_section(id),
+
+            ("GET", ["tasks"]) => self.list_tasks(query),
+            ("POST", ["tasks"]) => self.create_task(body),
+            ("GET", ["tasks", id]) => self.get_task(id),
+            ("POST", ["tasks", id]) => self.update_task(id, body),
+            ("POST", ["tasks", id, "close"]) => self.set_task_completed(id, true),
+            ("POST", ["tasks", id, "reopen"]) => self.set_task_completed(id, false),
+            ("DELETE", ["tasks", id]) => self.delete_task(id),
+
+            ("GET", ["labels"]) => Ok(self.list_labels()),
+            ("POST", ["labels"]) => self.create_label(body),
+            ("GET", ["labels", "shared"]) => Ok(self.list_shared_labels(query)),
+            ("POST", ["labels", "shared", "rename"]) => self.rename_shared_label(body),
+            ("POST", ["labels", "shared", "remove"]) => self.remove_shared_label(body),
+            ("GET", ["labels", id]) => self.get_label(id),
+            ("POST", ["labels", id]) => self.update_label(id, body),
+            ("DELETE", ["labels", id]) => self.delete_label(id),
+
+            ("GET", ["comments"]) => self.list_comments(query),
+            ("POST", ["comments"]) => self.create_comment(body),
+            ("GET", ["comments", id]) => self.get_comment(id),
+            ("POST", ["comments", id]) => self.update_comment(id, body),
+            ("DELETE", ["comments", id]) => self.delete_comment(id),
+
+            _ => Err(Reply::not_found("Path")),
+        };
+        result.unwrap_or_else(|reply| reply)
+    }
+
+    /// Generate a new object ID.
+    fn new_id(&mut self) -> String {
+        self.next_id += 1;
+        self.next_id.to_string()
+    }
+
+
+    // OAuth
+
+    fn exchange_code(&mut self, body: &Value) -> Result<Reply, Reply> {
+        required_str(body, "client_id")?;
+        required_str(body, "client_secret")?;
+        if required_str(body, "code")? != FAKE_AUTHORIZATION_CODE {
+            return Err(Reply::bad_request("bad_authorization_code"));
+        }
+        self.issued_tokens += 1;
+        let token = format!("fake-oauth-token-{}", self.issued_tokens);
+        self.tokens.insert(token.clone());
+        Ok(Reply::Json(200, json!({"access_token": token, "token_type": "Bearer"})))
+    }
+
+    fn revoke_token(&mut self, body: &Value) -> Result<Reply, Reply> {
+        required_str(body, "client_id")?;
+        required_str(body, "client_secret")?;
+        self.tokens.remove(&required_str(body, "access_token")?);
+        Ok(Reply::Empty)
+    }
+
+
+    // Projects
+
+    fn list_projects(&self) -> Reply {
+        let projects = self.projects.iter()
+            .filter(|p| !self.archived.contains(id_of(p)))
+            .map(|p| self.render_project(p))
+            .collect();
+        Reply::Json(200, Value::Array(projects))
+    }
+
+    fn get_project(&self, id: &str) -> Result<Reply, Reply> {
+        let project = find(&self.projects, id).ok_or_else(|| Reply::not_found("Project"))?;
+        Ok(Reply::Json(200, self.render_project(project)))
+    }
+
+    fn list_collaborators(&self, id: &str) -> Result<Reply, Reply> {
+        find(&self.projects, id).ok_or_else(|| Reply::not_found("Project"))?;
+        Ok(Reply::Json(200, json!([{"id": FAKE_USER_ID, "name": FAKE_USER_NAME, "email": FAKE_USER_EMAIL}])))
+    }
+
+    fn create_project(&mut self, body: &Value) -> Result<Reply, Reply> {
+        let name = required_str(body, "name")?;
+        let parent_id = optional_str(body, "parent_id")?;
+        if let Some(parent_id) = &parent_id {
+            find(&self.projects, parent_id).ok_or_else(|| Reply::bad_request("Parent project not found"))?;
+        }
+        let order = self.projects.iter().filter(|p| p["parent_id"].as_str() == parent_id.as_deref()).count();
+
+        let id = self.new_id();
+        let project = object(json!({
+            "id": id,
+            "name": name,
+            "color": body.get("color").filter(|c| !c.is_null()).cloned().unwrap_or(json!("charcoal")),
+            "parent_id": parent_id,
+            "order": order,
+            "is_shared": false,
+            "is_favorite": body.get("is_favorite").filter(|f| !f.is_null()).cloned().unwrap_or(json!(false)),
+            "is_inbox_project": false,
+            "view_style": body.get("view_style").filter(|v| !v.is_null()).cloned().unwrap_or(json!("list")),
+            "url": format!("https://todoist.com/showProject?id={}", id),
+        }));
+        let reply = Reply::Json(200, self.render_project(&project));
+        self.projects.push(project);
+        Ok(reply)
+    }
+
+    fn update_project(&mut self, id: &str, body: &Value) -> Result<Reply, Reply> {
+        let index = position(&self.projects, id).ok_or_else(|| Reply::not_found("Project"))?;
+        merge(&mut self.projects[index], body, &["name", "color", "is_favorite", "view_style"]);
+        Ok(Reply::Json(200, self.render_project(&self.projects[index])))
+    }
+
+    fn delete_project(&mut self, id: &str) -> Result<Reply, Reply> {
+        let project = find(&self.projects, id).ok_or_else(|| Reply::not_found("Project"))?;
+        if project["is_inbox_project"] == true {
+            return Err(Reply::bad_request("The inbox project can't be deleted"));
+        }
+
+        // Delete the project's descendants too, along with everything in any of them
+        let doomed = self.project_tree(id);
+        let in_doomed = |object: &Object| object["project_id"].as_str().is_some_and(|p| doomed.contains(p));
+        let doomed_tasks: HashSet<String> = self.tasks.iter()
+            .filter(|t| in_doomed(t))
+            .map(|t| id_of(t).to_string())
+            .collect();
+
+        self.comments.retain(|c| !in_doomed(c) && !c["task_id"].as_str().is_some_and(|t| doomed_tasks.contains(t)));
+        self.tasks.retain(|t| !in_doomed(t));
+        self.sections.retain(|s| !in_doomed(s));
+        self.projects.retain(|p| !doomed.contains(id_of(p)));
+        Ok(Reply::Empty)
+    }
+
+    /// The IDs of the project and all its descendants.
+    fn project_tree(&self, id: &str) -> HashSet<String> {
+        let mut tree = HashSet::from([id.to_string()]);
+        loop {
+            let children: Vec<String> = self.projects.iter()
+                .filter(|p| p["parent_id"].as_str().is_some_and(|parent| tree.contains(parent)))
+                .map(|p| id_of(p).to_string())
+                .filter(|child| !tree.contains(child))
+                .collect();
+            if children.is_empty() {
+                return tree;
+            }
+            tree.extend(children);
+        }
+    }
+
+    /// Archive or unarchive a project, along with its descendants.
+    fn set_project_archived(&mut self, args: &Value, archived: bool) -> Result<(), CommandFailure> {
+        let id = args["id"].as_str().unwrap_or_default();
+        let project = find(&self.projects, id).ok_or((21, "Project not found"))?;
+        if project["is_inbox_project"] == true {
+            return Err((20, "Invalid argument value"));
+        }
+        for id in self.project_tree(id) {
+            if archived {
+                self.archived.insert(id);
+            } else {
+                self.archived.remove(&id);
+            }
+        }
+        Ok(())
+    }
+
+    /// Move a project under another one, or to the top level if `parent_id` is null.
+    fn move_project(&mut self, args: &Value) -> Result<(), CommandFailure> {
+        let id = args["id"].as_str().unwrap_or_default();
+        let index = position(&self.projects, id).ok_or((21, "Project not found"))?;
+        let parent_id = args["parent_id"].as_str();
+        if let Some(parent_id) = parent_id {
+            find(&self.projects, parent_id).ok_or((21, "Project not found"))?;
+            if self.project_tree(id).contains(parent_id) {
+                return Err((20, "Invalid argument value"));
+            }
+        }
+        self.projects[index].insert("parent_id".to_string(), json!(parent_id));
+        Ok(())
+    }
+
+    /// The archived projects, in the form the Sync API gives them.
+    fn list_archived_projects(&self) -> Reply {
+        let projects = self.projects.iter()
+            .filter(|p| self.archived.contains(id_of(p)))
+            .map(|p| json!({
+                "id": p["id"],
+                "name": p["name"],
+                "color": p["color"],
+                "parent_id": p["parent_id"],
+                "child_order": p["order"],
+                "shared": p["is_shared"],
+                "is_favorite": p["is_favorite"],
+                "inbox_project": p["is_inbox_project"],
+                "view_style": p["view_style"],
+                "is_archived": true,
+                "is_deleted": false,
+            }))
+            .collect();
+        Reply::Json(200, Value::Array(projects))
+    }
+
+    fn render_project(&self, project: &Object) -> Value {
+        let mut project = project.clone();
+        let comment_count = self.comments.iter().filter(|c| c["project_id"] == project["id"]).count();
+        project.insert("comment_count".to_string(), json!(comment_count));
+        Value::Object(project)
+    }
+
+
+    // Sections
+
+    fn list_sections(&self, query: &HashMap<String, String>) -> Reply {
+        let sections = self.sections.iter()
+            .filter(|s| query.get("project_id").is_none_or(|p| s["project_id"] == p.as_str()))
+            .map(|s| Value::Object(s.clone()))
+            .collect();
+        Reply::Json(200, Value::Array(sections))
+    }
+
+    fn get_section(&self, id: &str) -> Result<Reply, Reply> {
+        let section = find(&self.sections, id).ok_or_else(|| Reply::not_found("Section"))?;
+        Ok(Reply::Json(200, Value::Object(section.clone())))
+    }
+
+    fn create_section(&mut self, body: &Value) -> Result<Reply, Reply> {
+        let name = required_str(body, "name")?;
+        let project_id = required_str(body, "project_id")?;
+        find(&self.projects, &project_id).ok_or_else(|| Reply::bad_request("Project not found"))?;
+        let order = match body.get("order").and_then(Value::as_i64) {
+            Some(order) => order,
+            None => self.sections.iter().filter(|s| s["project_id"] == project_id.as_str()).count() as i64 + 1,
+        };
+
+        let section = object(json!({
+            "id": self.new_id(),
+            "project_id": project_id,
+            "order": order,
+            "name": name,
+        }));
+        self.sections.push(section.clone());
+        Ok(Reply::Json(200, Value::Object(section)))
+    }
+
+    fn update_section(&mut self, id: &str, body: &Value) -> Result<Reply, Reply> {
+        let index = position(&self.sections, id).ok_or_else(|| Reply::not_found("Section"))?;
+        merge(&mut self.sections[index], body, &["name"]);
+        Ok(Reply::Json(200, Value::Object(self.sections[index].clone())))
+    }
+
+    fn delete_section(&mut self, id: &str) -> Result<Reply, Reply> {
+        position(&self.sections, id).ok_or_else(|| Reply::not_found("Section"))?;
+        let doomed_tasks: Vec<String> = self.tasks.iter()
+            .filter(|t| t["section_id"] == id)
+            .map(|t| id_of(t).to_string())
+            .collect();
+        for task_id in doomed_tasks {
+            self.remove_task_tree(&task_id);
+        }
+        self.sections.retain(|s| id_of(s) != id);
+        Ok(Reply::Empty)
+    }
+
+
+    // Tasks
+
+    fn list_tasks(&self, query: &HashMap<String, String>) -> Result<Reply, Reply> {
+        if query.contains_key("filter") {
+            return Err(Reply::bad_request("Filter queries aren't supported by the fake server"));
+        }
+        let ids: Option<Vec<&str>> = query.get("ids").map(|ids| ids.split(',').collect());
+
+        let tasks = self.tasks.iter()
+            .filter(|t| t["is_completed"] == false)
+            .filter(|t| ids.as_ref().is_none_or(|ids| ids.contains(&id_of(t))))
+            .filter(|t| query.get("project_id").is_none_or(|p| t["project_id"] == p.as_str()))
+            .filter(|t| query.get("section_id").is_none_or(|s| t["section_id"] == s.as_str()))
+            .filter(|t| query.get("label").is_none_or(|l| has_label(t, l)))
+            .map(|t| self.render_task(t))
+            .collect();
+        Ok(Reply::Json(200, Value::Array(tasks)))
+    }
+
+    fn get_task(&self, id: &str) -> Result<Reply, Reply> {
+        let task = find(&self.tasks, id).ok_or_else(|| Reply::not_found("Task"))?;
+        Ok(Reply::Json(200, self.render_task(task)))
+    }
+
+    fn create_task(&mut self, body: &Value) -> Result<Reply, Reply> {
+        let content = required_str(body, "content")?;
+        let mut project_id = optional_str(body, "project_id")?;
+        let mut section_id = optional_str(body, "section_id")?;
+        let parent_id = optional_str(body, "parent_id")?;
+
+        // Subtasks go wherever their parent is, and tasks in a section go in its project
+        if let Some(parent_id) = &parent_id {
+            let parent = find(&self.tasks, parent_id).ok_or_else(|| Reply::bad_request("Parent task not found"))?;
+            project_id = parent["project_id"].as_str().map(String::from);
+            section_id = parent["section_id"].as_str().map(String::from);
+        } else if let Some(section_id) = &section_id {
+            let section = find(&self.sections, section_id).ok_or_else(|| Reply::bad_request("Section not found"))?;
+            project_id = section["project_id"].as_str().map(String::from);
+        }
+        let project_id = project_id.unwrap_or_else(|| self.inbox_id());
+        find(&self.projects, &project_id).ok_or_else(|| Reply::bad_request("Project not found"))?;
+
+        let order = match body.get("order").and_then(Value::as_i64) {
+            Some(order) => order,
+            None => self.tasks.iter()
+                .filter(|t| t["project_id"] == project_id.as_str() && t["parent_id"].as_str() == parent_id.as_deref())
+                .count() as i64 + 1,
+        };
+        let assignee_id = optional_str(body, "assignee_id")?;
+
+        let id = self.new_id();
+        let mut task = object(json!({
+            "id": id,
+            "project_id": project_id,
+            "section_id": section_id,
+            "content": content,
+            "description": optional_str(body, "description")?.unwrap_or_default(),
+            "is_completed": false,
+            "labels": body.get("labels").filter(|l| l.is_array()).cloned().unwrap_or(json!([])),
+            "parent_id": parent_id,
+            "order": order,
+            "priority": body.get("priority").and_then(Value::as_u64).unwrap_or(1),
+            "due": null,
+            "url": format!("https://todoist.com/showTask?id={}", id),
+            "created_at": timestamp(),
+            "creator_id": FAKE_USER_ID,
+            "assigner_id": assignee_id.as_ref().map(|_| FAKE_USER_ID),
+            "assignee_id": assignee_id,
+            "duration": null,
+        }));
+        apply_due_and_duration(&mut task, body);
+
+        let reply = Reply::Json(200, self.render_task(&task));
+        self.tasks.push(task);
+        Ok(reply)
+    }
+
+    fn update_task(&mut self, id: &str, body: &Value) -> Result<Reply, Reply> {
+        let index = position(&self.tasks, id).ok_or_else(|| Reply::not_found("Task"))?;
+        let task = &mut self.tasks[index];
+        merge(task, body, &["content", "description", "labels", "priority", "assignee_id"]);
+        if body.get("assignee_id").is_some_and(|a| !a.is_null()) {
+            task.insert("assigner_id".to_string(), json!(FAKE_USER_ID));
+        }
+        apply_due_and_duration(task, body);
+        Ok(Reply::Json(200, self.render_task(&self.tasks[index])))
+    }
+
+    fn set_task_completed(&mut self, id: &str, completed: bool) -> Result<Reply, Reply> {
+        position(&self.tasks, id).ok_or_else(|| Reply::not_found("Task"))?;
+
+        // Closing a task closes its subtasks too, but reopening only reopens the task itself
+        let affected = if completed { self.task_tree(id) } else { vec![id.to_string()] };
+        for task in self.tasks.iter_mut().filter(|t| affected.iter().any(|a| a == id_of(t))) {
+            task.insert("is_completed".to_string(), json!(completed));
+        }
+        Ok(Reply::Empty)
+    }
+
+    fn delete_task(&mut self, id: &str) -> Result<Reply, Reply> {
+        position(&self.tasks, id).ok_or_else(|| Reply::not_found("Task"))?;
+        self.remove_task_tree(id);
+        Ok(Reply::Empty)
+    }
+
+    /// The IDs of the task and all its subtasks, recursively.
+    fn task_tree(&self, id: &str) -> Vec<String> {
+        let mut tree = vec![id.to_string()];
+        let mut next = 0;
+        while next < tree.len() {
+            let parent = tree[next].clone();
+            tree.extend(self.tasks.iter().filter(|t| t["parent_id"] == parent.as_str()).map(|t| id_of(t).to_string()));
+            next += 1;
+        }
+        tree
+    }
+
+    /// Remove the task, its subtasks and all their comments.
+    fn remove_task_tree(&mut self, id: &str) {
+        let tree = self.task_tree(id);
+        let in_tree = |id: Option<&str>| id.is_some_and(|id| tree.iter().any(|t| t == id));
+        self.comments.retain(|c| !in_tree(c["task_id"].as_str()));
+        self.reminders.retain(|r| !in_tree(r["item_id"].as_str()));
+        self.tasks.retain(|t| !in_tree(Some(id_of(t))));
+    }
+
+    /// Move a task and its subtasks to a project, section or parent, as given by exactly one of
+    /// the fields of `args`.
+    fn move_task(&mut self, args: &Value) -> Result<(), CommandFailure> {
+        let id = args["id"].as_str().unwrap_or_default();
+        find(&self.tasks, id).ok_or((22, "Item not found"))?;
+        let tree = self.task_tree(id);
+
+        let (project_id, section_id, parent_id) = if let Some(project_id) = args["project_id"].as_str() {
+            find(&self.projects, project_id).ok_or((21, "Project not found"))?;
+            (project_id.to_string(), Value::Null, Value::Null)
+        } else if let Some(section_id) = args["section_id"].as_str() {
+            let section = find(&self.sections, section_id).ok_or((40, "Section not found"))?;
+            (section["project_id"].as_str().unwrap_or_default().to_string(), json!(section_id), Value::Null)
+        } else if let Some(parent_id) = args["parent_id"].as_str() {
+            let parent = find(&self.tasks, parent_id).ok_or((22, "Item not found"))?;
+            if tree.iter().any(|t| t == parent_id) {
+                return Err((20, "Invalid argument value"));
+            }
+            (parent["project_id"].as_str().unwrap_or_default().to_string(), parent["section_id"].clone(), json!(parent_id))
+        } else {
+            return Err((19, "Required argument is missing"));
+        };
+
+        for task in self.tasks.iter_mut().filter(|t| tree.iter().any(|id| id == id_of(t))) {
+            task.insert("project_id".to_string(), json!(project_id));
+            task.insert("section_id".to_string(), section_id.clone());
+            if id_of(task) == id {
+                task.insert("parent_id".to_string(), parent_id.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// Create a task from Quick Add text, taking out `#Project` (if there is such a project),
+    /// `@label` and `p1` to `p4`. Due dates aren't recognised, so are left in the content.
+    fn quick_add(&mut self, body: &Value) -> Result<Reply, Reply> {
+        let text = required_str(body, "text")?;
+        let mut content = Vec::new();
+        let mut task = json!({"labels": []});
+        for word in text.split_whitespace() {
+            let project = word.strip_prefix('#')
+                .and_then(|name| self.projects.iter().find(|p| p["name"] == name));
+            if let Some(project) = project {
+                task["project_id"] = project["id"].clone();
+            } else if let Some(label) = word.strip_prefix('@') {
+                task["labels"].as_array_mut().unwrap().push(json!(label));
+            } else if let Some(priority) = ["p4", "p3", "p2", "p1"].iter().position(|p| *p == word) {
+                task["priority"] = json!(priority + 1);
+            } else {
+                content.push(word);
+            }
+        }
+        task["content"] = json!(content.join(" "));
+
+        let Reply::Json(_, task) = self.create_task(&task)? else {
+            unreachable!("Creating a task always replies with it");
+        };
+        if let Some(note) = optional_str(body, "note")? {
+            self.create_comment(&json!({"task_id": task["id"], "content": note}))?;
+        }
+        Ok(Reply::Json(200, json!({
+            "id": task["id"],
+            "project_id": task["project_id"],
+            "section_id": task["section_id"],
+            "content": task["content"],
+            "description": task["description"],
+            "checked": false,
+            "labels": task["labels"],
+            "parent_id": task["parent_id"],
+            "child_order": task["order"],
+            "priority": task["priority"],
+            "due": null,
+            "added_at": task["created_at"],
+            "added_by_uid": FAKE_USER_ID,
+            "responsible_uid": null,
+            "assigned_by_uid": null,
+            "duration": null,
+        })))
+    }
+
+    fn render_task(&self, task: &Object) -> Value {
+        let mut task = task.clone();
+        let comment_count = self.comments.iter().filter(|c| c["task_id"] == task["id"]).count();
+        task.insert("comment_count".to_string(), json!(comment_count));
+        Value::Object(task)
+    }
+
+
+    // Labels
+
+    fn list_labels(&self) -> Reply {
+        Reply::Json(200, Value::Array(self.labels.iter().cloned().map(Value::Object).collect()))
+    }
+
+    fn get_label(&self, id: &str) -> Result<Reply, Reply> {
+        let label = find(&self.labels, id).ok_or_else(|| Reply::not_found("Label"))?;
+        Ok(Reply::Json(200, Value::Object(label.clone())))
+    }
+
+    fn create_label(&mut self, body: &Value) -> Result<Reply, Reply> {
+        let name = required_str(body, "name")?;
+        if self.labels.iter().any(|l| l["name"] == name.as_str()) {
+            return Err(Reply::bad_request("Label already exists"));
+        }
+        let order = match body.get("order").and_then(Value::as_i64) {
+            Some(order) => order,
+            None => self.labels.len() as i64 + 1,
+        };
+
+        let label = object(json!({
+            "id": self.new_id(),
+            "name": name,
+            "color": body.get("color").filter(|c| !c.is_null()).cloned().unwrap_or(json!("charcoal")),
+            "order": order,
+            "is_favorite": body.get("is_favorite").filter(|f| !f.is_null()).cloned().unwrap_or(json!(false)),
+        }));
+        self.labels.push(label.clone());
+        Ok(Reply::Json(200, Value::Object(label)))
+    }
+
+    fn update_label(&mut self, id: &str, body: &Value) -> Result<Reply, Reply> {
+        let index = position(&self.labels, id).ok_or_else(|| Reply::not_found("Label"))?;
+
+        // Renaming a personal label renames it on every task too
+        let old_name = self.labels[index]["name"].as_str().unwrap_or_default().to_string();
+        if let Some(new_name) = optional_str(body, "name")? {
+            self.rename_on_tasks(&old_name, &new_name);
+        }
+        merge(&mut self.labels[index], body, &["name", "order", "color", "is_favorite"]);
+        Ok(Reply::Json(200, Value::Object(self.labels[index].clone())))
+    }
+
+    fn delete_label(&mut self, id: &str) -> Result<Reply, Reply> {
+        let label = find(&self.labels, id).ok_or_else(|| Reply::not_found("Label"))?;
+        let name = label["name"].as_str().unwrap_or_default().to_string();
+        self.remove_from_tasks(&name);
+        self.labels.retain(|l| id_of(l) != id);
+        Ok(Reply::Empty)
+    }
+
+    /// The names of labels on active tasks, leaving out personal labels if asked to.
+    fn list_shared_labels(&self, query: &HashMap<String, String>) -> Reply {
+        let omit_personal = query.get("omit_personal").is_some_and(|o| o == "true");
+        let mut names: Vec<&str> = Vec::new();
+        for task in self.tasks.iter().filter(|t| t["is_completed"] == false) {
+            for name in task["labels"].as_array().into_iter().flatten().filter_map(Value::as_str) {
+                let omitted = omit_personal && self.labels.iter().any(|l| l["name"] == name);
+                if !omitted && !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        Reply::Json(200, json!(names))
+    }
+
+    fn rename_shared_label(&mut self, body: &Value) -> Result<Reply, Reply> {
+        let old_name = required_str(body, "old_name")?;
+        let new_name = required_str(body, "new_name")?;
+        self.rename_on_tasks(&old_name, &new_name);
+        Ok(Reply::Empty)
+    }
+
+    fn remove_shared_label(&mut self, body: &Value) -> Result<Reply, Reply> {
+        let name = required_str(body, "name")?;
+        self.remove_from_tasks(&name);
+        Ok(Reply::Empty)
+    }
+
+    fn rename_on_tasks(&mut self, old_name: &str, new_name: &str) {
+        for labels in self.tasks.iter_mut().filter_map(|t| t.get_mut("labels")?.as_array_mut()) {
+            for label in labels.iter_mut().filter(|l| *l == old_name) {
+                *label = json!(new_name);
+            }
+        }
+    }
+
+    fn remove_from_tasks(&mut self, name: &str) {
+        for labels in self.tasks.iter_mut().filter_map(|t| t.get_mut("labels")?.as_array_mut()) {
+            labels.retain(|l| l != name);
+        }
+    }
+
+
+    // Comments
+
+    fn list_comments(&self, query: &HashMap<String, String>) -> Result<Reply, Reply> {
+        let (field, id) = match (query.get("task_id"), query.get("project_id")) {
+            (Some(task_id), _) => ("task_id", task_id),
+            (None, Some(project_id)) => ("project_id", project_id),
+            (None, None) => return Err(Reply::bad_request("Either task_id or project_id is required")),
+        };
+        let comments = self.comments.iter()
+            .filter(|c| c[field] == id.as_str())
+            .cloned()
+            .map(Value::Object)
+            .collect();
+        Ok(Reply::Json(200, Value::Array(comments)))
+    }
+
+    fn get_comment(&self, id: &str) -> Result<Reply, Reply> {
+        let comment = find(&self.comments, id).ok_or_else(|| Reply::not_found("Comment"))?;
+        Ok(Reply::Json(200, Value::Object(comment.clone())))
+    }
+
+    fn create_comment(&mut self, body: &Value) -> Result<Reply, Reply> {
+        let content = required_str(body, "content")?;
+        let task_id = optional_str(body, "task_id")?;
+        let project_id = optional_str(body, "project_id")?;
+        match (&task_id, &project_id) {
+            (Some(task_id), None) => {
+                find(&self.tasks, task_id).ok_or_else(|| Reply::bad_request("Task not found"))?;
+            }
+            (None, Some(project_id)) => {
+                find(&self.projects, project_id).ok_or_else(|| Reply::bad_request("Project not found"))?;
+            }
+            _ => return Err(Reply::bad_request("Exactly one of task_id and project_id is required")),
+        }
+
+        let comment = object(json!({
+            "id": self.new_id(),
+            "task_id": task_id,
+            "project_id": project_id,
+            "posted_at": timestamp(),
+            "content": content,
+            "attachment": body.get("attachment").cloned().unwrap_or(Value::Null),
+        }));
+        self.comments.push(comment.clone());
+        Ok(Reply::Json(200, Value::Object(comment)))
+    }
+
+    fn update_comment(&mut self, id: &str, body: &Value) -> Result<Reply, Reply> {
+        let index = position(&self.comments, id).ok_or_else(|| Reply::not_found("Comment"))?;
+        merge(&mut self.comments[index], body, &["content"]);
+        Ok(Reply::Json(200, Value::Object(self.comments[index].clone())))
+    }
+
+    fn delete_comment(&mut self, id: &str) -> Result<Reply, Reply> {
+        position(&self.comments, id).ok_or_else(|| Reply::not_found("Comment"))?;
+        self.comments.retain(|c| id_of(c) != id);
+        Ok(Reply::Empty)
+    }
+
+
+    // Filters
+
+    fn get_filter(&self, query: &HashMap<String, String>) -> Result<Reply, Reply> {
+        let id = query.get("filter_id").ok_or_else(|| Reply::bad_request("filter_id is required"))?;
+        let filter = find(&self.filters, id).ok_or_else(|| Reply::not_found("Filter"))?;
+        Ok(Reply::Json(200, json!({"filter": filter})))
+    }
+
+    fn add_filter(&mut self, args: &Value) -> Result<String, CommandFailure> {
+        let (Some(name), Some(query)) = (args["name"].as_str(), args["query"].as_str()) else {
+            return Err((19, "Required argument is missing"));
+        };
+        let id = self.new_id();
+        let order = args["item_order"].as_i64().unwrap_or(self.filters.len() as i64 + 1);
+        self.filters.push(object(json!({
+            "id": id,
+            "name": name,
+            "query": query,
+            "color": args.get("color").cloned().unwrap_or(json!("charcoal")),
+            "item_order": order,
+            "is_favorite": args.get("is_favorite").cloned().unwrap_or(json!(false)),
+            "is_deleted": false,
+        })));
+        Ok(id)
+    }
+
+    fn update_filter(&mut self, args: &Value) -> Result<(), CommandFailure> {
+        let id = args["id"].as_str().unwrap_or_default();
+        let index = position(&self.filters, id).ok_or((22, "Filter not found"))?;
+        merge(&mut self.filters[index], args, &["name", "query", "color", "item_order", "is_favorite"]);
+        Ok(())
+    }
+
+    fn reorder_filters(&mut self, args: &Value) -> Result<(), CommandFailure> {
+        let orders = args["id_order_mapping"].as_object().ok_or((19, "Required argument is missing"))?;
+        if orders.keys().any(|id| find(&self.filters, id).is_none()) {
+            return Err((22, "Filter not found"));
+        }
+        for filter in &mut self.filters {
+            if let Some(order) = orders.get(id_of(filter)) {
+                filter.insert("item_order".to_string(), order.clone());
+            }
+        }
+        Ok(())
+    }
+
+    fn delete_filter(&mut self, args: &Value) -> Result<(), CommandFailure> {
+        let id = args["id"].as_str().unwrap_or_default();
+        find(&self.filters, id).ok_or((22, "Filter not found"))?;
+        self.filters.retain(|f| id_of(f) != id);
+        Ok(())
+    }
+
+
+    // Reminders
+
+    fn add_reminder(&mut self, args: &Value) -> Result<String, CommandFailure> {
+        let task_id = args["item_id"].as_str().ok_or((19, "Required argument is missing"))?;
+        find(&self.tasks, task_id).ok_or((22, "Item not found"))?;
+        let mut reminder = object(json!({
+            "id": self.new_id(),
+            "item_id": task_id,
+            "notify_uid": args.get("notify_uid").cloned().unwrap_or(json!(FAKE_USER_ID)),
+            "is_deleted": false,
+        }));
+        set_reminder_kind(&mut reminder, args)?;
+        let id = id_of(&reminder).to_string();
+        self.reminders.push(reminder);
+        Ok(id)
+    }
+
+    fn update_reminder(&mut self, args: &Value) -> Result<(), CommandFailure> {
+        let id = args["id"].as_str().unwrap_or_default();
+        let index = position(&self.reminders, id).ok_or((22, "Reminder not found"))?;
+        if args.get("type").is_some() {
+            set_reminder_kind(&mut self.reminders[index], args)?;
+        }
+        merge(&mut self.reminders[index], args, &["notify_uid"]);
+        Ok(())
+    }
+
+    fn delete_reminder(&mut self, args: &Value) -> Result<(), CommandFailure> {
+        let id = args["id"].as_str().unwrap_or_default();
+        find(&self.reminders, id).ok_or((22, "Reminder not found"))?;
+        self.reminders.retain(|r| id_of(r) != id);
+        Ok(())
+    }
+
+
+    // Sync API
+
+    /// Answer a read through `/sync` as a full sync, whatever the sync token. Only the resource
+    /// types the REST API doesn't cover are supported; any others are left out.
+    fn sync_read(&self, body: &Value) -> Result<Reply, Reply> {
+        let resource_types = required_str(body, "resource_types")?;
+        let resource_types: Vec<String> = serde_json::from_str(&resource_types)
+            .map_err(|err| Reply::bad_request(&format!("Invalid resource types: {}", err)))?;
+
+        let mut reply = object(json!({"sync_token": "fake-sync-token", "full_sync": true}));
+        let all = |objects: &[Object]| Value::Array(objects.iter().cloned().map(Value::Object).collect());
+        for resource_type in resource_types {
+            let value = match resource_type.as_str() {
+                "filters" => all(&self.filters),
+                "reminders" => all(&self.reminders),
+                "user" => json!({
+                    "id": FAKE_USER_ID,
+                    "full_name": FAKE_USER_NAME,
+                    "email": FAKE_USER_EMAIL,
+                    "tz_info": {"timezone": "UTC", "gmt_string": "+00:00", "hours": 0, "minutes": 0, "is_dst": 0},
+                    "start_day": 1,
+                    "is_premium": false,
+                }),
+                _ => continue,
+            };
+            reply.insert(resource_type, value);
+        }
+        Ok(Reply::Json(200, Value::Object(reply)))
+    }
+
+    /// Apply each of the commands in a request to `/sync`, answering with the status of each and
+    /// the real IDs of any objects they created.
+    fn sync_commands(&mut self, body: &Value) -> Result<Reply, Reply> {
+        let commands = required_str(body, "commands")?;
+        let commands: Vec<Value> = serde_json::from_str(&commands)
+            .map_err(|err| Reply::bad_request(&format!("Invalid commands: {}", err)))?;
+
+        let mut status = Map::new();
+        let mut temp_id_mapping = Map::new();
+        for command in &commands {
+            let args = &command["args"];
+            let result = match command["type"].as_str().unwrap_or_default() {
+                "item_move" => self.move_task(args).map(|()| None),
+                "project_archive" => self.set_project_archived(args, true).map(|()| None),
+                "project_unarchive" => self.set_project_archived(args, false).map(|()| None),
+                "project_move" => self.move_project(args).map(|()| None),
+                "filter_add" => self.add_filter(args).map(Some),
+                "filter_update" => self.update_filter(args).map(|()| None),
+                "filter_update_orders" => self.reorder_filters(args).map(|()| None),
+                "filter_delete" => self.delete_filter(args).map(|()| None),
+                "reminder_add" => self.add_reminder(args).map(Some),
+                "reminder_update" => self.update_reminder(args).map(|()| None),
+                "reminder_delete" => self.delete_reminder(args).map(|()| None),
+                _ => Err((1, "Invalid command type")),
+            };
+            let result = result.map(|created| {
+                if let (Some(id), Some(temp_id)) = (created, command["temp_id"].as_str()) {
+                    temp_id_mapping.insert(temp_id.to_string(), json!(id));
+                }
+            });
+            let uuid = command["uuid"].as_str().unwrap_or_default().to_string();
+            status.insert(uuid, match result {
+                Ok(()) => json!("ok"),
+                Err((error_code, error)) => json!({"error_code": error_code, "error": error}),
+            });
+        }
+        Ok(Reply::Json(200, json!({"sync_status": status, "temp_id_mapping": temp_id_mapping})))
+    }
+}
+
+
+/// Why a Sync API command failed: Todoist's error code and message.
+type CommandFailure = (u32, &'static str);
+
+
+/// Set the type of a reminder, and the fields which go with it, from the arguments of a command.
+fn set_reminder_kind(reminder: &mut Object, args: &Value) -> Result<(), CommandFailure> {
+    let fields: &[&str] = match args["type"].as_str() {
+        Some("relative") => &["minute_offset"],
+        Some("absolute") => &["due"],
+        Some("location") => &["name", "loc_lat", "loc_long", "loc_trigger"],
+        _ => return Err((19, "Required argument is missing")),
+    };
+    if fields.iter().any(|field| args[*field].is_null()) {
+        return Err((19, "Required argument is missing"));
+    }
+    for field in ["minute_offset", "due", "name", "loc_lat", "loc_long", "loc_trigger", "radius"] {
+        reminder.remove(field);
+    }
+    merge(reminder, args, &["type", "minute_offset", "due", "name", "loc_lat", "loc_long", "loc_trigger", "radius"]);
+    Ok(())
+}
+
+
+/// Parse the body of a request as JSON, or as a form if it isn't JSON, treating an empty body as
+/// an empty object.
+fn parse_body(body: &[u8]) -> Result<Value, Reply> {
+    let text = String::from_utf8_lossy(body);
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(json!({}));
+    }
+    if !text.starts_with('{') && !text.starts_with('[') {
+        // Forms use the same encoding as query strings, so any URL will do for parsing one
+        let url = reqwest::Url::parse(&format!("http://localhost/?{}", text))
+            .map_err(|err| Reply::bad_request(&format!("Invalid form body: {}", err)))?;
+        return Ok(Value::Object(url.query_pairs().map(|(name, value)| (name.into_owned(), json!(value))).collect()));
+    }
+    serde_json::from_slice(body).map_err(|err| Reply::bad_request(&format!("Invalid JSON body: {}", err)))
+}
+
+/// Unwrap a `json!` object literal.
+fn object(value: Value) -> Object {
+    match value {
+        Value::Object(object) => object,
+        _ => unreachable!("Fake objects are always built from object literals"),
+    }
+}
+
+fn id_of(object: &Object) -> &str {
+    object["id"].as_str().unwrap_or_default()
+}
+
+fn position(objects: &[Object], id: &str) -> Option<usize> {
+    objects.iter().position(|o| id_of(o) == id)
+}
+
+fn find<'a>(objects: &'a [Object], id: &str) -> Option<&'a Object> {
+    objects.iter().find(|o| id_of(o) == id)
+}
+
+fn has_label(task: &Object, label: &str) -> bool {
+    task["labels"].as_array().is_some_and(|labels| labels.iter().any(|l| l == label))
+}
+
+/// A string field of the body, or `None` if it is missing or null.
+fn optional_str(body: &Value, field: &str) -> Result<Option<String>, Reply> {
+    match body.get(field) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(value)) => Ok(Some(value.clone())),
+        Some(_) => Err(Reply::bad_request(&format!("Argument {} must be a string", field))),
+    }
+}
+
+/// A string field of the body, which must be present.
+fn required_str(body: &Value, field: &str) -> Result<String, Reply> {
+    optional_str(body, field)?
+        .ok_or_else(|| Reply::bad_request(&format!("Required argument is missing: {}", field)))
+}
+
+/// Copy the given fields from the body to the object, where the body has a non-null value.
+fn merge(object: &mut Object, body: &Value, fields: &[&str]) {
+    for field in fields {
+        if let Some(value) = body.get(*field).filter(|v| !v.is_null()) {
+            object.insert(field.to_string(), value.clone());
+        }
+    }
+}
+
+/// Set the task's `due` and `duration` from the `due_*` and `duration*` fields of the body, if it
+/// has them.
+fn apply_due_and_duration(task: &mut Object, body: &Value) {
+    let text = |field: &str| body.get(field).and_then(Value::as_str);
+
+    let due = if let Some(datetime) = text("due_datetime") {
+        Some(json!({
+            "string": datetime,
+            "date": datetime.get(..10).unwrap_or(datetime),
+            "is_recurring": false,
+            "datetime": datetime,
+            "timezone": null,
+        }))
+    } else if let Some(date) = text("due_date") {
+        Some(json!({"string": date, "date": date, "is_recurring": false, "datetime": null, "timezone": null}))
+    } else if let Some(string) = text("due_string") {
+        // Natural language dates aren't understood, so anything except clearing it is due today
+        if string == "no date" || string == "no due date" {
+            Some(Value::Null)
+        } else {
+            Some(json!({
+                "string": string,
+                "date": timestamp().get(..10),
+                "is_recurring": string.starts_with("every"),
+                "datetime": null,
+                "timezone": null,
+            }))
+        }
+    } else {
+        None
+    };
+    if let Some(due) = due {
+        task.insert("due".to_string(), due);
+    }
+
+    if let (Some(amount), Some(unit)) = (body.get("duration").and_then(Value::as_u64), text("duration_unit")) {
+        task.insert("duration".to_string(), json!({"amount": amount, "unit": unit}));
+    }
+}
+
+/// The current time, in the format Todoist uses, e.g. `2019-12-11T22:36:50.000000Z`.
+fn timestamp() -> String {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = now.as_secs() as i64;
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86400));
+    let time = seconds.rem_euclid(86400);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:06}Z",
+        year, month, day, time / 3600, time / 60 % 60, time % 60, now.subsec_micros()
+    )
+}
+
+/// Convert a number of days since 1970-01-01 to a (year, month, day) date, using Howard Hinnant's
+/// algorithm.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
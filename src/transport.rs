@@ -0,0 +1,336 @@
+//! The layer which actually sends HTTP requests, which can be swapped out e.g. for testing.
+//!
+//! Every API call goes through the client's `HttpTransport` (or `AsyncHttpTransport` for an
+//! `AsyncTodoistClient`). The client works out the full request — URL, headers and body — and
+//! hands it to the transport as an `HttpRequest`; the transport only has to send it and hand back
+//! the `HttpResponse`. Retrying, rate limiting and interpreting the response all happen in the
+//! client, so they work the same whatever the transport.
+//!
+//! By default the client uses `ReqwestTransport` (or `AsyncReqwestTransport`), which sends
+//! requests over the network. To use something else, give it to the client builder with
+//! `transport()` (or `async_transport()`).
+//!
+//! `MockTransport` is an in-memory transport for tests. Script the responses it should give, in
+//! order, then inspect the requests it was sent:
+//! ```
+//! use todoist_v2_rest::{TodoistClient, projects};
+//! use todoist_v2_rest::transport::{HttpResponse, MockTransport};
+//! use serde_json::json;
+//!
+//! let transport = MockTransport::new();
+//! transport.push_response(HttpResponse::json(200, &json!([])));
+//!
+//! let client = TodoistClient::builder("token")
+//!     .transport(transport.clone())
+//!     .build()
+//!     .unwrap();
+//! let user_projects = projects::get_projects(&client).unwrap();
+//!
+//! assert!(user_projects.is_empty());
+//! assert_eq!(transport.requests()[0].url, "https://api.todoist.com/rest/v2/projects");
+//! ```
+//!
+//! To record real interactions with Todoist and replay them later, see the `cassette` module.
+
+use std::collections::VecDeque;
+#[cfg(feature = "async")]
+use std::future::Future;
+#[cfg(feature = "async")]
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Method, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::err::TodoistAPIError;
+
+
+/// A fully-formed HTTP request, as handed to a transport to send.
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    /// The HTTP method.
+    pub method: Method,
+
+    /// The full URL, including any query string.
+    pub url: String,
+
+    /// The headers to send, including `Authorization`.
+    pub headers: Vec<(String, String)>,
+
+    /// The body to send, if any.
+    pub body: Option<Vec<u8>>,
+}
+
+impl HttpRequest {
+    /// The value of the named header, if the request has it. Header names are case-insensitive.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Deserialise the body of the request as JSON, if it has one.
+    pub fn json<T: DeserializeOwned>(&self) -> Option<T> {
+        serde_json::from_slice(self.body.as_ref()?).ok()
+    }
+}
+
+
+/// An HTTP response, as handed back by a transport.
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    /// The status of the response.
+    pub status: StatusCode,
+
+    /// The headers of the response.
+    pub headers: HeaderMap,
+
+    /// The body of the response.
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// A response with the given status and body, and no headers.
+    ///
+    /// # Panics
+    /// If `status` isn't a valid HTTP status code (100-999).
+    pub fn new(status: u16, body: &[u8]) -> HttpResponse {
+        HttpResponse {
+            status: StatusCode::from_u16(status).expect("Invalid HTTP status code"),
+            headers: HeaderMap::new(),
+            body: body.to_vec(),
+        }
+    }
+
+    /// A response with the given status and a JSON body.
+    ///
+    /// # Panics
+    /// If `status` isn't a valid HTTP status code, or `body` can't be serialised.
+    pub fn json<T: Serialize + ?Sized>(status: u16, body: &T) -> HttpResponse {
+        let body = serde_json::to_vec(body).expect("Couldn't serialise response body");
+        HttpResponse::new(status, &body).with_header("Content-Type", "application/json")
+    }
+
+    /// Add a header to the response.
+    ///
+    /// # Panics
+    /// If the name or value isn't valid in an HTTP header.
+    pub fn with_header(mut self, name: &str, value: &str) -> HttpResponse {
+        self.headers.append(
+            HeaderName::from_bytes(name.as_bytes()).expect("Invalid header name"),
+            HeaderValue::from_str(value).expect("Invalid header value"),
+        );
+        self
+    }
+
+    /// The body of the response as text, replacing any invalid UTF-8.
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+
+/// Sends HTTP requests for a `TodoistClient`.
+pub trait HttpTransport: Send + Sync {
+    /// Send the request and return the response, whatever its status. Only return an error if no
+    /// response could be obtained at all.
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, TodoistAPIError>;
+}
+
+
+/// A future returned by an `AsyncHttpTransport`.
+#[cfg(feature = "async")]
+pub type TransportFuture<'a> = Pin<Box<dyn Future<Output = Result<HttpResponse, TodoistAPIError>> + Send + 'a>>;
+
+/// Sends HTTP requests for an `AsyncTodoistClient`. Only available with the `async` feature.
+#[cfg(feature = "async")]
+pub trait AsyncHttpTransport: Send + Sync {
+    /// Send the request and return the response, whatever its status. Only return an error if no
+    /// response could be obtained at all.
+    fn send<'a>(&'a self, request: &'a HttpRequest) -> TransportFuture<'a>;
+}
+
+
+/// The default transport, which sends requests over the network using a pooled blocking reqwest
+/// client.
+pub struct ReqwestTransport {
+    client: reqwest::blocking::Client,
+}
+
+impl ReqwestTransport {
+    /// Wrap an existing reqwest client.
+    pub fn new(client: reqwest::blocking::Client) -> ReqwestTransport {
+        ReqwestTransport { client }
+    }
+
+    /// Create a transport with the given timeouts and proxy.
+    pub(crate) fn with_settings(
+        timeout: Option<Duration>,
+        connect_timeout: Option<Duration>,
+        proxy: Option<reqwest::Proxy>,
+    ) -> Result<ReqwestTransport, TodoistAPIError> {
+        let mut builder = reqwest::blocking::Client::builder()
+            .timeout(timeout)
+            .connect_timeout(connect_timeout);
+        if let Some(proxy) = proxy {
+            builder = builder.proxy(proxy);
+        }
+        let client = builder.build().map_err(TodoistAPIError::ClientBuildError)?;
+        Ok(ReqwestTransport::new(client))
+    }
+}
+
+impl HttpTransport for ReqwestTransport {
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, TodoistAPIError> {
+        let mut builder = self.client.request(request.method.clone(), &request.url);
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+        if let Some(body) = &request.body {
+            builder = builder.body(body.clone());
+        }
+
+        let response = builder.send().map_err(TodoistAPIError::ReqwestRequestError)?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response.bytes().map_err(TodoistAPIError::ReqwestRequestError)?;
+        Ok(HttpResponse { status, headers, body: body.to_vec() })
+    }
+}
+
+
+/// The default asynchronous transport, which sends requests over the network using a pooled
+/// reqwest client. Only available with the `async` feature.
+#[cfg(feature = "async")]
+pub struct AsyncReqwestTransport {
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "async")]
+impl AsyncReqwestTransport {
+    /// Wrap an existing reqwest client.
+    pub fn new(client: reqwest::Client) -> AsyncReqwestTransport {
+        AsyncReqwestTransport { client }
+    }
+
+    /// Create a transport with the given timeouts and proxy.
+    pub(crate) fn with_settings(
+        timeout: Option<Duration>,
+        connect_timeout: Option<Duration>,
+        proxy: Option<reqwest::Proxy>,
+    ) -> Result<AsyncReqwestTransport, TodoistAPIError> {
+        let mut builder = reqwest::Client::builder();
+        if let Some(timeout) = timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(proxy) = proxy {
+            builder = builder.proxy(proxy);
+        }
+        let client = builder.build().map_err(TodoistAPIError::ClientBuildError)?;
+        Ok(AsyncReqwestTransport::new(client))
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncHttpTransport for AsyncReqwestTransport {
+    fn send<'a>(&'a self, request: &'a HttpRequest) -> TransportFuture<'a> {
+        Box::pin(async move {
+            let mut builder = self.client.request(request.method.clone(), &request.url);
+            for (name, value) in &request.headers {
+                builder = builder.header(name, value);
+            }
+            if let Some(body) = &request.body {
+                builder = builder.body(body.clone());
+            }
+
+            let response = builder.send().await.map_err(TodoistAPIError::ReqwestRequestError)?;
+            let status = response.status();
+            let headers = response.headers().clone();
+            let body = response.bytes().await.map_err(TodoistAPIError::ReqwestRequestError)?;
+            Ok(HttpResponse { status, headers, body: body.to_vec() })
+        })
+    }
+}
+
+
+/// An in-memory transport which gives scripted responses and records the requests it is sent.
+///
+/// Clones share the same script and record, so keep a clone to inspect after giving one to the
+/// client builder. It works as both a blocking and (with the `async` feature) an asynchronous
+/// transport.
+#[derive(Debug, Clone, Default)]
+pub struct MockTransport {
+    state: Arc<Mutex<MockState>>,
+}
+
+/// The shared state of a `MockTransport`.
+#[derive(Debug, Default)]
+struct MockState {
+    responses: VecDeque<Result<HttpResponse, String>>,
+    requests: Vec<HttpRequest>,
+}
+
+impl MockTransport {
+    /// Create a transport with no scripted responses.
+    pub fn new() -> MockTransport {
+        MockTransport::default()
+    }
+
+    /// Add a response to the end of the script.
+    pub fn push_response(&self, response: HttpResponse) {
+        self.lock().responses.push_back(Ok(response));
+    }
+
+    /// Add a failure to get any response (like a dropped connection) to the end of the script.
+    pub fn push_failure(&self, message: &str) {
+        self.lock().responses.push_back(Err(message.to_string()));
+    }
+
+    /// All the requests sent so far, in order.
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.lock().requests.clone()
+    }
+
+    /// The number of scripted responses which haven't been used yet.
+    pub fn remaining_responses(&self) -> usize {
+        self.lock().responses.len()
+    }
+
+    /// Record the request and pop the next scripted response. Once the script has run out, every
+    /// request fails.
+    fn next_response(&self, request: &HttpRequest) -> Result<HttpResponse, TodoistAPIError> {
+        let mut state = self.lock();
+        state.requests.push(request.clone());
+        match state.responses.pop_front() {
+            Some(Ok(response)) => Ok(response),
+            Some(Err(message)) => Err(TodoistAPIError::TransportError(message)),
+            None => Err(TodoistAPIError::TransportError(format!(
+                "MockTransport has no response scripted for {} {}", request.method, request.url
+            ))),
+        }
+    }
+
+    /// Lock the state, ignoring poisoning since a panicking test shouldn't hide other failures.
+    fn lock(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl HttpTransport for MockTransport {
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, TodoistAPIError> {
+        self.next_response(request)
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncHttpTransport for MockTransport {
+    fn send<'a>(&'a self, request: &'a HttpRequest) -> TransportFuture<'a> {
+        Box::pin(async move { self.next_response(request) })
+    }
+}
@@ -0,0 +1,67 @@
+//! Functions for finding out about the user whose API token is being used.
+//!
+//! - To get the user's profile (name, email address, timezone etc.), use `get_user_profile()`
+//! - To get the user's karma and how many tasks they have completed recently, use
+//!   `get_productivity_stats()`
+//!
+//! Since `get_user_profile()` is a cheap request which any valid token can make, it is also a
+//! good way to check a token: it returns `TodoistAPIError::Unauthorized` if the token is invalid
+//! or has been revoked.
+//! ```no_run
+//! use todoist_v2_rest::{TodoistUser, user};
+//! let todoist_user = TodoistUser::new("a2a72c2f394b265bb798d5dc4ef55be51443d519");
+//! let profile = user::get_user_profile(&todoist_user).expect("Invalid token");
+//! println!("Logged in as {} ({})", profile.name, profile.timezone);
+//! ```
+
+
+mod structs;
+
+pub use structs::user_profile::UserProfile;
+pub use structs::productivity_stats::{DayCompletions, Goals, ProductivityStats, Streak, WeekCompletions};
+
+use serde::Deserialize;
+use crate::err::TodoistAPIError;
+use crate::general::{get_from_response, ApiRequest};
+use crate::sync;
+use crate::TodoistUser;
+#[cfg(feature = "async")]
+use crate::AsyncTodoistClient;
+
+
+/// Get the profile of the user whose token is being used.
+pub fn get_user_profile(user: &TodoistUser) -> Result<UserProfile, TodoistAPIError> {
+    // Send the API request
+    let response = user.send(sync::resource_request("user"));
+    get_from_response::<UserResponse>(response).map(|response| response.user)
+}
+
+/// Asynchronous version of `get_user_profile()`.
+#[cfg(feature = "async")]
+pub async fn get_user_profile_async(client: &AsyncTodoistClient) -> Result<UserProfile, TodoistAPIError> {
+    let response = client.send(sync::resource_request("user")).await;
+    get_from_response::<UserResponse>(response).map(|response| response.user)
+}
+
+
+/// Get the user's karma, how many tasks they have completed on recent days and in recent weeks,
+/// and their streaks of meeting their daily and weekly goals.
+pub fn get_productivity_stats(user: &TodoistUser) -> Result<ProductivityStats, TodoistAPIError> {
+    // Send the API request
+    let response = user.send(ApiRequest::sync_get("/completed/get_stats"));
+    get_from_response(response)
+}
+
+/// Asynchronous version of `get_productivity_stats()`.
+#[cfg(feature = "async")]
+pub async fn get_productivity_stats_async(client: &AsyncTodoistClient) -> Result<ProductivityStats, TodoistAPIError> {
+    let response = client.send(ApiRequest::sync_get("/completed/get_stats")).await;
+    get_from_response(response)
+}
+
+
+/// The response to a sync which only asks for the user.
+#[derive(Deserialize)]
+struct UserResponse {
+    user: UserProfile,
+}
@@ -0,0 +1,5 @@
+/// Defines the `UserProfile` struct.
+pub mod user_profile;
+
+/// Defines the `ProductivityStats` struct and the types used within it.
+pub mod productivity_stats;
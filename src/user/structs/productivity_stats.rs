@@ -0,0 +1,74 @@
+use serde::Deserialize;
+
+/// The user's karma and how many tasks they have completed recently, as returned by
+/// `get_productivity_stats()`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProductivityStats {
+    /// The user's karma score.
+    pub karma: f64,
+
+    /// Whether the user's karma is going `up` or `down`.
+    pub karma_trend: String,
+
+    /// How many tasks the user has completed in total.
+    pub completed_count: u32,
+
+    /// How many tasks the user completed on each of the last few days, most recent first.
+    #[serde(rename = "days_items")]
+    pub days: Vec<DayCompletions>,
+
+    /// How many tasks the user completed in each of the last few weeks, most recent first.
+    #[serde(rename = "week_items")]
+    pub weeks: Vec<WeekCompletions>,
+
+    /// The user's goals, and their streaks of meeting them.
+    pub goals: Goals,
+}
+
+/// How many tasks the user completed on a day.
+#[derive(Debug, Clone, Deserialize)]
+#[allow(missing_docs)]
+pub struct DayCompletions {
+    /// The day, as `YYYY-MM-DD`.
+    pub date: String,
+    pub total_completed: u32,
+}
+
+/// How many tasks the user completed in a week.
+#[derive(Debug, Clone, Deserialize)]
+#[allow(missing_docs)]
+pub struct WeekCompletions {
+    /// The first day of the week, as `YYYY-MM-DD`.
+    pub from: String,
+    /// The last day of the week, as `YYYY-MM-DD`.
+    pub to: String,
+    pub total_completed: u32,
+}
+
+/// How many tasks the user aims to complete each day and week, and how long they have managed it
+/// for.
+#[derive(Debug, Clone, Deserialize)]
+#[allow(missing_docs)]
+pub struct Goals {
+    pub daily_goal: u32,
+    pub weekly_goal: u32,
+    pub current_daily_streak: Streak,
+    pub current_weekly_streak: Streak,
+    pub max_daily_streak: Streak,
+    pub max_weekly_streak: Streak,
+}
+
+/// A run of consecutive days or weeks in which the user met their goal.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Streak {
+    /// How many days or weeks the streak lasted.
+    pub count: u32,
+
+    /// The first day of the streak, as `YYYY-MM-DD`, or an empty string if there is no streak.
+    #[serde(default)]
+    pub start: String,
+
+    /// The last day of the streak, as `YYYY-MM-DD`, or an empty string if there is no streak.
+    #[serde(default)]
+    pub end: String,
+}
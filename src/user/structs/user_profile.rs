@@ -0,0 +1,36 @@
+use serde::{Deserialize, Deserializer};
+
+/// The account details of the user whose token is being used, as returned by `get_user_profile()`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct UserProfile {
+    /// The user's ID, as used for e.g. a task's `creator_id` or `assignee_id`.
+    pub id: String,
+
+    /// The user's full name.
+    #[serde(rename = "full_name")]
+    pub name: String,
+
+    /// The user's email address.
+    pub email: String,
+
+    /// The user's timezone, as an IANA name such as `Europe/London`. Todoist gives due times
+    /// without a timezone in this one.
+    #[serde(rename = "tz_info", deserialize_with = "timezone_name")]
+    pub timezone: String,
+
+    /// The day the user's week starts on, from 1 (Monday) to 7 (Sunday).
+    pub start_day: u8,
+
+    /// Whether the user has Todoist Pro.
+    pub is_premium: bool,
+}
+
+/// Todoist's description of the user's timezone, of which only the name is kept.
+#[derive(Deserialize)]
+struct TimezoneInfo {
+    timezone: String,
+}
+
+fn timezone_name<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    TimezoneInfo::deserialize(deserializer).map(|info| info.timezone)
+}